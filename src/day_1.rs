@@ -1,42 +1,211 @@
 use anyhow;
 
-use std::io;
-use std::io::prelude::*;
 use std::fs;
 
-pub fn calculate_fuel (filepath: String) -> anyhow::Result<()> {
-    let file = io::BufReader::new(fs::File::open(filepath)?);
+pub fn calculate_fuel (filepath: String, lenient: bool) -> anyhow::Result<()> {
+    let masses = get_masses(filepath, lenient)?;
+    let total_fuel = checked_total(masses.into_iter().map(|(line, mass)| (line, fuel(mass))), "fuel")?;
 
-    let mut total_fuel: usize = 0;
+    println!("Total fuel required: {}", total_fuel);
+
+    Ok(())
+}
 
-    for line in file.lines() {
-        let mass = line?.parse::<usize>()?;
-        total_fuel += mass/3-2;
-    }
+
+pub fn calculate_fuel_extended (filepath: String, lenient: bool) -> anyhow::Result<()> {
+    let masses = get_masses(filepath, lenient)?;
+    let total_fuel = checked_total(masses.into_iter().map(|(line, mass)| (line, fuel_chain(mass).iter().sum())), "fuel")?;
 
     println!("Total fuel required: {}", total_fuel);
 
     Ok(())
 }
 
+// Masses of the modules with their line numbers. Every invalid line is reported at once, or only
+// logged and skipped when lenient. Empty lines are ignored.
+fn get_masses(filepath: String, lenient: bool) -> anyhow::Result<Vec<(usize, usize)>> {
+    parse_masses(&fs::read_to_string(filepath)?, lenient)
+}
 
-pub fn calculate_fuel_extended (filepath: String) -> anyhow::Result<()> {
-    let file = io::BufReader::new(fs::File::open(filepath)?);
+fn parse_masses(text: &str, lenient: bool) -> anyhow::Result<Vec<(usize, usize)>> {
+    let mut masses = Vec::new();
+    let mut errors = Vec::new();
 
-    let mut total_fuel: usize = 0;
+    for (i, line) in text.lines().enumerate() {
+        let text = line.trim();
+        if text.is_empty() {
+            continue;
+        }
 
-    for line in file.lines() {
-        let mut mass = line?.parse::<usize>()? as isize;
+        match text.parse::<usize>() {
+            Ok(mass) => masses.push((i + 1, mass)),
+            Err(error) => errors.push(format!("Line {}: invalid mass \"{}\": {}", i + 1, text, error)),
+        }
+    }
 
-        loop {
-            mass = mass/3-2;
-            if mass <= 0 { break; }
+    if !errors.is_empty() {
+        if !lenient {
+            return Err(anyhow!("Invalid masses:\n{}", errors.join("\n")));
+        }
 
-            total_fuel += mass as usize;
+        for error in errors.iter() {
+            warn!("{}, skipped", error);
         }
     }
 
-    println!("Total fuel required: {}", total_fuel);
+    Ok(masses)
+}
+
+// Sums the values of the lines, failing at the line which overflows the total
+fn checked_total(values: impl Iterator<Item = (usize, usize)>, what: &str) -> anyhow::Result<usize> {
+    let mut total: usize = 0;
+
+    for (line, value) in values {
+        total = total.checked_add(value).ok_or(anyhow!("Line {}: total {} overflows", line, what))?;
+    }
+
+    Ok(total)
+}
+
+// Modules too light to need fuel take none instead of a negative amount
+fn fuel(mass: usize) -> usize {
+    (mass / 3).saturating_sub(2)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Csv,
+    Json,
+}
+
+struct Module {
+    line: usize,
+    mass: usize,
+    chain: Vec<usize>, // fuel for the mass, then fuel for that fuel and so on
+}
+
+impl Module {
+    fn fuel(&self) -> usize {
+        self.chain.first().copied().unwrap_or(0)
+    }
+
+    fn recursive_fuel(&self) -> usize {
+        self.chain.iter().sum()
+    }
+}
+
+// Lists the fuel of every module, so the modules dominating the budget are easy to spot
+pub fn fuel_report(filepath: String, format: Format, lenient: bool) -> anyhow::Result<()> {
+    let modules: Vec<Module> = get_masses(filepath, lenient)?.into_iter()
+        .map(|(line, mass)| Module { line, mass, chain: fuel_chain(mass) })
+        .collect();
+
+    print!("{}", match format {
+        Format::Table => report_table(&modules)?,
+        Format::Csv => report_csv(&modules)?,
+        Format::Json => report_json(&modules)?,
+    });
 
     Ok(())
 }
+
+fn fuel_chain(mass: usize) -> Vec<usize> {
+    let mut chain = vec![fuel(mass)];
+
+    loop {
+        let fuel = fuel(chain[chain.len() - 1]);
+        if fuel == 0 { break; }
+
+        chain.push(fuel);
+    }
+
+    chain
+}
+
+fn report_table(modules: &[Module]) -> anyhow::Result<String> {
+    let mut text = format!("{:>6} {:>10} {:>10} {:>10}  Chain\n", "Line", "Mass", "Fuel", "Recursive");
+
+    for module in modules.iter() {
+        let chain: Vec<String> = module.chain.iter().map(|fuel| fuel.to_string()).collect();
+        text.push_str(&format!("{:>6} {:>10} {:>10} {:>10}  {}\n", module.line, module.mass, module.fuel(), module.recursive_fuel(), chain.join(" + ")));
+    }
+
+    let (mass, fuel, recursive) = totals(modules)?;
+    text.push_str(&format!("{:>6} {:>10} {:>10} {:>10}\n", "Total", mass, fuel, recursive));
+
+    Ok(text)
+}
+
+fn report_csv(modules: &[Module]) -> anyhow::Result<String> {
+    let mut text = String::from("line,mass,fuel,recursive_fuel,chain\n");
+
+    for module in modules.iter() {
+        let chain: Vec<String> = module.chain.iter().map(|fuel| fuel.to_string()).collect();
+        text.push_str(&format!("{},{},{},{},{}\n", module.line, module.mass, module.fuel(), module.recursive_fuel(), chain.join(" ")));
+    }
+
+    let (mass, fuel, recursive) = totals(modules)?;
+    text.push_str(&format!("total,{},{},{},\n", mass, fuel, recursive));
+
+    Ok(text)
+}
+
+fn report_json(modules: &[Module]) -> anyhow::Result<String> {
+    let entries: Vec<String> = modules.iter()
+        .map(|module| {
+            let chain: Vec<String> = module.chain.iter().map(|fuel| fuel.to_string()).collect();
+            format!("{{\"line\":{},\"mass\":{},\"fuel\":{},\"recursive_fuel\":{},\"chain\":[{}]}}",
+                module.line, module.mass, module.fuel(), module.recursive_fuel(), chain.join(","))
+        })
+        .collect();
+
+    let (mass, fuel, recursive) = totals(modules)?;
+
+    Ok(format!("{{\"modules\":[{}],\"total\":{{\"mass\":{},\"fuel\":{},\"recursive_fuel\":{}}}}}\n", entries.join(","), mass, fuel, recursive))
+}
+
+// Total mass, fuel and recursive fuel
+fn totals(modules: &[Module]) -> anyhow::Result<(usize, usize, usize)> {
+    Ok((
+        checked_total(modules.iter().map(|module| (module.line, module.mass)), "mass")?,
+        checked_total(modules.iter().map(|module| (module.line, module.fuel())), "fuel")?,
+        checked_total(modules.iter().map(|module| (module.line, module.recursive_fuel())), "recursive fuel")?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn light_modules_need_no_fuel() {
+        for mass in 0..=8 {
+            assert_eq!(fuel(mass), 0, "mass {}", mass);
+            assert_eq!(fuel_chain(mass), vec![0], "mass {}", mass);
+        }
+
+        assert_eq!(fuel(14), 2);
+        assert_eq!(fuel_chain(14), vec![2]);
+        assert_eq!(fuel_chain(1969), vec![654, 216, 70, 21, 5]);
+    }
+
+    #[test]
+    fn every_invalid_line_is_reported() {
+        let text = "12\nheavy\n\n-3\n14\n";
+
+        let error = parse_masses(text, false).unwrap_err().to_string();
+        assert!(error.contains("Line 2: invalid mass \"heavy\""), "{}", error);
+        assert!(error.contains("Line 4: invalid mass \"-3\""), "{}", error);
+
+        assert_eq!(parse_masses(text, true).unwrap(), vec![(1, 12), (5, 14)]);
+    }
+
+    #[test]
+    fn overflowing_total_names_the_line() {
+        let masses = parse_masses(&format!("{0}\n{0}\n{0}\n{0}\n", usize::MAX), false).unwrap();
+
+        let error = checked_total(masses.into_iter().map(|(line, mass)| (line, fuel(mass))), "fuel").unwrap_err();
+        assert_eq!(error.to_string(), "Line 4: total fuel overflows");
+    }
+}
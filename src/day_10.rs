@@ -0,0 +1,115 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::fs;
+
+// Asteroid vaporised as the answer to the second part
+const BET: usize = 200;
+
+type Position = (isize, isize);
+type Direction = (isize, isize);
+
+pub fn best_location(filepath: String) -> anyhow::Result<()> {
+    let asteroids = get_asteroids(filepath)?;
+
+    let (station, visible) = find_station(&asteroids).ok_or(anyhow!("Map has no asteroids"))?;
+    println!("Best location is {:?} with {} asteroids detected", station, visible);
+
+    Ok(())
+}
+
+pub fn vaporisation_order(filepath: String, print_order: bool) -> anyhow::Result<()> {
+    let asteroids = get_asteroids(filepath)?;
+
+    let (station, _) = find_station(&asteroids).ok_or(anyhow!("Map has no asteroids"))?;
+    let order = vaporise(&asteroids, station);
+
+    if print_order {
+        for (i, (x, y)) in order.iter().enumerate() {
+            println!("{}: ({}, {})", i + 1, x, y);
+        }
+    }
+
+    let (x, y) = order.get(BET - 1)
+        .ok_or(anyhow!("Laser at {:?} vaporises only {} asteroids", station, order.len()))?;
+    println!("Asteroid number {} is at ({}, {}), answer is {}", BET, x, y, x * 100 + y);
+
+    Ok(())
+}
+
+fn get_asteroids(filepath: String) -> anyhow::Result<Vec<Position>> {
+    let mut asteroids = Vec::new();
+
+    for (y, line) in fs::read_to_string(filepath)?.lines().enumerate() {
+        for (x, c) in line.trim().chars().enumerate() {
+            match c {
+                '#' => asteroids.push((x as isize, y as isize)),
+                '.' => {},
+                _ => return Err(anyhow!("Unknown map symbol {:?} at ({}, {})", c, x, y)),
+            }
+        }
+    }
+
+    Ok(asteroids)
+}
+
+fn gcd(a: isize, b: isize) -> isize {
+    if b == 0 { a.abs() } else { gcd(b, a % b) }
+}
+
+// Asteroids in line from the station share the direction reduced by the gcd
+fn direction(from: Position, to: Position) -> Direction {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let divisor = gcd(dx, dy);
+
+    (dx / divisor, dy / divisor)
+}
+
+// Returns the asteroid seeing the most others and how many it sees
+fn find_station(asteroids: &[Position]) -> Option<(Position, usize)> {
+    asteroids.iter()
+        .map(|station| {
+            let directions: HashSet<Direction> = asteroids.iter()
+                .filter(|asteroid| *asteroid != station)
+                .map(|asteroid| direction(*station, *asteroid))
+                .collect();
+
+            (*station, directions.len())
+        })
+        .max_by_key(|(_, visible)| *visible)
+}
+
+// Clockwise order of directions starting from straight up. Y axis points down.
+fn compare_clockwise(a: &Direction, b: &Direction) -> Ordering {
+    // Right half, straight up included, comes before the left half
+    let half = |d: &Direction| if d.0 > 0 || (d.0 == 0 && d.1 < 0) { 0 } else { 1 };
+
+    half(a).cmp(&half(b)).then_with(|| (b.0 * a.1 - b.1 * a.0).cmp(&0))
+}
+
+// Laser turns clockwise from straight up and vaporises the closest asteroid in every direction
+// per rotation
+fn vaporise(asteroids: &[Position], station: Position) -> Vec<Position> {
+    let mut lines: BTreeMap<Direction, Vec<Position>> = BTreeMap::new();
+    for asteroid in asteroids.iter().filter(|asteroid| **asteroid != station) {
+        lines.entry(direction(station, *asteroid)).or_default().push(*asteroid);
+    }
+
+    let mut lines: Vec<(Direction, Vec<Position>)> = lines.into_iter().collect();
+    lines.sort_by(|a, b| compare_clockwise(&a.0, &b.0));
+    // Furthest first, so the closest one is popped
+    for (_, line) in lines.iter_mut() {
+        line.sort_by_key(|(x, y)| -((x - station.0).abs() + (y - station.1).abs()));
+    }
+
+    let mut order = Vec::new();
+    while order.len() < asteroids.len() - 1 {
+        for (_, line) in lines.iter_mut() {
+            if let Some(asteroid) = line.pop() {
+                order.push(asteroid);
+            }
+        }
+    }
+
+    order
+}
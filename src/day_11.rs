@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::day_2;
+use crate::intcode::{Event, Machine};
+
+const BLACK: isize = 0;
+const WHITE: isize = 1;
+
+struct Hull {
+    colours: HashMap<(isize, isize), isize>, // panels missing from the map are black
+    painted: HashSet<(isize, isize)>, // panels painted at least once
+}
+
+pub fn count_painted_panels(filepath: String) -> anyhow::Result<()> {
+    let hull = paint_hull(day_2::get_instructions(filepath)?, BLACK)?;
+
+    println!("Robot painted {} panels at least once", hull.painted.len());
+
+    Ok(())
+}
+
+pub fn registration_identifier(filepath: String) -> anyhow::Result<()> {
+    let hull = paint_hull(day_2::get_instructions(filepath)?, WHITE)?;
+
+    let white: Vec<&(isize, isize)> = hull.colours.iter().filter(|(_, colour)| **colour == WHITE).map(|(panel, _)| panel).collect();
+    if white.is_empty() {
+        println!("Nothing was painted white");
+        return Ok(());
+    }
+
+    let (min_x, max_x) = (white.iter().map(|p| p.0).min().unwrap(), white.iter().map(|p| p.0).max().unwrap());
+    let (min_y, max_y) = (white.iter().map(|p| p.1).min().unwrap(), white.iter().map(|p| p.1).max().unwrap());
+
+    for y in min_y..=max_y {
+        let row: String = (min_x..=max_x)
+            .map(|x| if hull.colours.get(&(x, y)) == Some(&WHITE) { '█' } else { ' ' })
+            .collect();
+
+        println!("{}", row);
+    }
+
+    Ok(())
+}
+
+// Runs the robot starting on a panel of the given colour. Y axis points down.
+fn paint_hull(program: Vec<isize>, start_colour: isize) -> anyhow::Result<Hull> {
+    let mut machine = Machine::new(program);
+    let mut hull = Hull { colours: HashMap::new(), painted: HashSet::new() };
+
+    let mut position = (0, 0);
+    let mut direction = (0, -1); // facing up
+    let mut paint: Option<isize> = None; // first output of a pair waits for the turn
+
+    hull.colours.insert(position, start_colour);
+
+    loop {
+        match machine.run()? {
+            Event::Input => {
+                machine.push_input(*hull.colours.get(&position).unwrap_or(&BLACK));
+            },
+            Event::Output(value) => {
+                match paint.take() {
+                    None => { paint = Some(value); },
+                    Some(colour) => {
+                        hull.colours.insert(position, colour);
+                        hull.painted.insert(position);
+
+                        direction = match value {
+                            0 => (direction.1, -direction.0), // left
+                            1 => (-direction.1, direction.0), // right
+                            turn => return Err(anyhow!("Unknown turn direction {}", turn)),
+                        };
+                        position = (position.0 + direction.0, position.1 + direction.1);
+                    },
+                }
+            },
+            Event::Halt => { break; },
+            Event::Watchpoint(_) => {},
+        }
+    }
+
+    Ok(hull)
+}
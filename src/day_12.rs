@@ -0,0 +1,131 @@
+use std::fs;
+
+const AXES: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Moon {
+    position: [isize; AXES],
+    velocity: [isize; AXES],
+}
+
+impl Moon {
+    fn energy(&self) -> isize {
+        let potential: isize = self.position.iter().map(|p| p.abs()).sum();
+        let kinetic: isize = self.velocity.iter().map(|v| v.abs()).sum();
+
+        potential * kinetic
+    }
+}
+
+pub fn total_energy(filepath: String, steps: usize) -> anyhow::Result<()> {
+    let mut moons = get_moons(filepath)?;
+
+    for _ in 0..steps {
+        step(&mut moons, 0..AXES);
+    }
+
+    let energy: isize = moons.iter().map(Moon::energy).sum();
+    println!("Total energy after {} steps is {}", steps, energy);
+
+    Ok(())
+}
+
+// Axes never influence each other, so the universe repeats after the least common multiple of the
+// periods of every axis. Every state has exactly one previous state, so the first repeated state
+// is the initial one.
+pub fn find_repetition(filepath: String) -> anyhow::Result<()> {
+    let initial = get_moons(filepath)?;
+
+    let mut steps: u64 = 1;
+    for axis in 0..AXES {
+        let mut moons = initial.clone();
+        let mut period: u64 = 0;
+
+        loop {
+            step(&mut moons, axis..axis+1);
+            period += 1;
+
+            if moons.iter().zip(initial.iter())
+                .all(|(moon, start)| moon.position[axis] == start.position[axis] && moon.velocity[axis] == start.velocity[axis]) {
+                break;
+            }
+        }
+
+        debug!("Axis {} repeats every {} steps", axis, period);
+        steps = lcm(steps, period)?;
+    }
+
+    println!("Universe repeats after {} steps", steps);
+
+    Ok(())
+}
+
+fn get_moons(filepath: String) -> anyhow::Result<Vec<Moon>> {
+    fs::read_to_string(filepath)?.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(parse_moon)
+        .collect()
+}
+
+// Parses "<x=-1, y=0, z=2>"
+fn parse_moon(line: &str) -> anyhow::Result<Moon> {
+    let inner = line.strip_prefix('<').and_then(|line| line.strip_suffix('>'))
+        .ok_or(anyhow!("Moon has to be enclosed in <>: {}", line))?;
+
+    let mut position = [0; AXES];
+    let mut coordinates = inner.split(',').map(str::trim);
+
+    for (axis, name) in ["x=", "y=", "z="].iter().enumerate() {
+        let coordinate = coordinates.next().ok_or(anyhow!("Moon is missing {} coordinate: {}", &name[..1], line))?;
+        position[axis] = coordinate.strip_prefix(name)
+            .ok_or(anyhow!("Expected {} coordinate, found {}: {}", &name[..1], coordinate, line))?
+            .parse::<isize>()?;
+    }
+
+    if coordinates.next().is_some() {
+        return Err(anyhow!("Moon has more than {} coordinates: {}", AXES, line));
+    }
+
+    Ok(Moon { position, velocity: [0; AXES] })
+}
+
+// Applies gravity between every pair of moons, then velocity, on the given axes
+fn step(moons: &mut [Moon], axes: std::ops::Range<usize>) {
+    for axis in axes.clone() {
+        for i in 0..moons.len() {
+            for j in i+1..moons.len() {
+                let pull = (moons[j].position[axis] - moons[i].position[axis]).signum();
+                moons[i].velocity[axis] += pull;
+                moons[j].velocity[axis] -= pull;
+            }
+        }
+    }
+
+    for moon in moons.iter_mut() {
+        for axis in axes.clone() {
+            moon.position[axis] += moon.velocity[axis];
+        }
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+// Periods of many bodies can combine into more steps than fit in 64 bits
+fn lcm(a: u64, b: u64) -> anyhow::Result<u64> {
+    (a / gcd(a, b)).checked_mul(b).ok_or(anyhow!("Least common multiple of {} and {} overflows", a, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lcm_of_periods() {
+        assert_eq!(lcm(18, 28).unwrap(), 252);
+        assert_eq!(lcm(252, 44).unwrap(), 2772);
+        assert!(lcm(u64::MAX, u64::MAX - 1).is_err());
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+
+use crate::day_2;
+use crate::intcode::{Event, Machine};
+
+const EMPTY: isize = 0;
+const WALL: isize = 1;
+const BLOCK: isize = 2;
+const PADDLE: isize = 3;
+const BALL: isize = 4;
+
+// Address holding the number of quarters inserted
+const QUARTERS_ADDRESS: usize = 0;
+const FREE_PLAY: isize = 2;
+
+// Pause between frames in watch mode
+const FRAME_DELAY: Duration = Duration::from_millis(30);
+
+#[derive(Default)]
+struct Screen {
+    tiles: HashMap<(isize, isize), isize>,
+    score: isize,
+    ball: Option<(isize, isize)>,
+    paddle: Option<(isize, isize)>,
+}
+
+impl Screen {
+    // Applies an (x, y, tile) triple, x = -1 and y = 0 carries the score instead
+    fn draw(&mut self, x: isize, y: isize, tile: isize) {
+        if (x, y) == (-1, 0) {
+            self.score = tile;
+            return;
+        }
+
+        match tile {
+            BALL => { self.ball = Some((x, y)); },
+            PADDLE => { self.paddle = Some((x, y)); },
+            _ => {},
+        }
+
+        self.tiles.insert((x, y), tile);
+    }
+
+    fn count(&self, tile: isize) -> usize {
+        self.tiles.values().filter(|t| **t == tile).count()
+    }
+
+    fn render(&self) -> String {
+        let max_x = self.tiles.keys().map(|p| p.0).max().unwrap_or(0);
+        let max_y = self.tiles.keys().map(|p| p.1).max().unwrap_or(0);
+
+        let mut screen = format!("Score: {}\n", self.score);
+        for y in 0..=max_y {
+            let row: String = (0..=max_x)
+                .map(|x| match self.tiles.get(&(x, y)).copied().unwrap_or(EMPTY) {
+                    WALL => '█',
+                    BLOCK => '▒',
+                    PADDLE => '▀',
+                    BALL => '●',
+                    _ => ' ',
+                })
+                .collect();
+
+            screen.push_str(&row);
+            screen.push('\n');
+        }
+
+        screen
+    }
+}
+
+pub fn count_blocks(filepath: String) -> anyhow::Result<()> {
+    let mut machine = Machine::new(day_2::get_instructions(filepath)?);
+    let mut screen = Screen::default();
+
+    for triple in machine.run_with_input(&[])?.chunks(3) {
+        if let [x, y, tile] = *triple {
+            screen.draw(x, y, tile);
+        }
+    }
+
+    print!("{}", screen.render());
+    println!("There are {} block tiles on the screen", screen.count(BLOCK));
+
+    Ok(())
+}
+
+// Plays the game by keeping the paddle under the ball. Watch mode redraws the screen every frame.
+pub fn play(filepath: String, watch: bool) -> anyhow::Result<()> {
+    let mut machine = Machine::new(day_2::get_instructions(filepath)?);
+    machine.poke(QUARTERS_ADDRESS, FREE_PLAY);
+
+    let mut screen = Screen::default();
+    let mut triple: Vec<isize> = Vec::with_capacity(3);
+
+    loop {
+        match machine.run()? {
+            Event::Output(value) => {
+                triple.push(value);
+
+                if let [x, y, tile] = triple[..] {
+                    screen.draw(x, y, tile);
+                    triple.clear();
+                }
+            },
+            // Game asks for the joystick once per frame
+            Event::Input => {
+                if watch {
+                    // Clear the terminal and move the cursor to the top left corner
+                    print!("\x1b[2J\x1b[H{}", screen.render());
+                    thread::sleep(FRAME_DELAY);
+                }
+
+                let joystick = match (screen.ball, screen.paddle) {
+                    (Some(ball), Some(paddle)) => (ball.0 - paddle.0).signum(),
+                    _ => 0,
+                };
+
+                machine.push_input(joystick);
+            },
+            Event::Halt => { break; },
+            Event::Watchpoint(_) => {},
+        }
+    }
+
+    if watch {
+        print!("\x1b[2J\x1b[H{}", screen.render());
+    }
+
+    match screen.count(BLOCK) {
+        0 => println!("All blocks are broken, final score is {}", screen.score),
+        blocks => println!("Game over with {} blocks left, final score is {}", blocks, screen.score),
+    }
+
+    Ok(())
+}
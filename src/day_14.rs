@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use std::fs;
+
+use itertools::Itertools;
+
+const ORE: &str = "ORE";
+const FUEL: &str = "FUEL";
+const CARGO_ORE: u64 = 1_000_000_000_000;
+
+struct Reaction {
+    quantity: u64, // produced by one reaction
+    inputs: Vec<(String, u64)>,
+}
+
+struct Factory {
+    reactions: HashMap<String, Reaction>, // by the produced chemical
+    order: Vec<String>, // every chemical comes before the chemicals it is made of, ORE last
+}
+
+pub fn ore_for_fuel(filepath: String) -> anyhow::Result<()> {
+    let factory = Factory::new(&fs::read_to_string(filepath)?)?;
+
+    println!("1 FUEL needs {} ORE", factory.ore_needed(1)?);
+
+    Ok(())
+}
+
+pub fn fuel_from_cargo(filepath: String) -> anyhow::Result<()> {
+    let factory = Factory::new(&fs::read_to_string(filepath)?)?;
+
+    println!("{} ORE produces {} FUEL", CARGO_ORE, factory.max_fuel(CARGO_ORE)?);
+
+    Ok(())
+}
+
+impl Factory {
+    fn new(text: &str) -> anyhow::Result<Factory> {
+        let mut reactions = HashMap::new();
+
+        for (i, line) in text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            let (chemical, reaction) = parse_reaction(line).map_err(|error| anyhow!("Line {}: {}", i + 1, error))?;
+
+            if chemical == ORE {
+                return Err(anyhow!("Line {}: {} can not be produced", i + 1, ORE));
+            }
+            if reactions.insert(chemical.to_owned(), reaction).is_some() {
+                return Err(anyhow!("Line {}: {} is produced by more than one reaction", i + 1, chemical));
+            }
+        }
+
+        let order = order(&reactions)?;
+
+        Ok(Factory { reactions, order })
+    }
+
+    // Most FUEL the ORE is enough for
+    fn max_fuel(&self, ore: u64) -> anyhow::Result<u64> {
+        // Leftovers make every FUEL after the first cheaper, so the first one gives a lower bound
+        let mut low = ore.checked_div(self.ore_needed(1)?).ok_or(anyhow!("{} is made without {}", FUEL, ORE))?;
+        let mut high = low.max(1).checked_mul(2).ok_or(anyhow!("Amount of {} overflows", FUEL))?;
+        while self.ore_needed(high)? <= ore {
+            high = high.checked_mul(2).ok_or(anyhow!("Amount of {} overflows", FUEL))?;
+        }
+
+        // Low is always affordable and high never is
+        while high - low > 1 {
+            let middle = low + (high - low) / 2;
+
+            if self.ore_needed(middle)? <= ore {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+
+        Ok(low)
+    }
+
+    // Runs the reactions from FUEL down to ORE. Every chemical is handled once all its consumers
+    // asked for it, so the reactions are rounded up only once.
+    fn ore_needed(&self, fuel: u64) -> anyhow::Result<u64> {
+        let mut needed: HashMap<&str, u64> = HashMap::new();
+        let mut leftovers: HashMap<&str, u64> = HashMap::new();
+        needed.insert(FUEL, fuel);
+
+        for chemical in self.order.iter().filter(|chemical| *chemical != ORE) {
+            let amount = needed.get(chemical.as_str()).copied().unwrap_or(0);
+            let reaction = &self.reactions[chemical];
+            let runs = amount.div_ceil(reaction.quantity);
+
+            let produced = runs.checked_mul(reaction.quantity).ok_or(anyhow!("Amount of {} overflows", chemical))?;
+            leftovers.insert(chemical, produced - amount);
+
+            for (input, quantity) in reaction.inputs.iter() {
+                let needed = needed.entry(input).or_insert(0);
+                *needed = runs.checked_mul(*quantity)
+                    .and_then(|total| needed.checked_add(total))
+                    .ok_or(anyhow!("Amount of {} overflows", input))?;
+            }
+        }
+
+        debug!("Leftovers: {:?}", leftovers.iter().filter(|(_, amount)| **amount > 0).collect::<Vec<_>>());
+
+        Ok(needed.get(ORE).copied().unwrap_or(0))
+    }
+}
+
+// Parses "7 A, 1 B => 1 C"
+fn parse_reaction(line: &str) -> anyhow::Result<(String, Reaction)> {
+    let mut sides = line.split("=>");
+    let (inputs, output) = match (sides.next(), sides.next(), sides.next()) {
+        (Some(inputs), Some(output), None) => (inputs, output),
+        _ => return Err(anyhow!("Reaction has to be \"inputs => output\": {}", line)),
+    };
+
+    let inputs = inputs.split(',').map(parse_amount).collect::<anyhow::Result<Vec<(String, u64)>>>()?;
+    let (chemical, quantity) = parse_amount(output)?;
+
+    Ok((chemical, Reaction { quantity, inputs }))
+}
+
+// Parses "7 A", a quantity of zero is rejected on either side of a reaction
+fn parse_amount(text: &str) -> anyhow::Result<(String, u64)> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+
+    let (quantity, chemical) = match words.as_slice() {
+        [quantity, chemical] => (quantity.parse::<u64>()?, chemical.to_string()),
+        _ => return Err(anyhow!("Expected quantity and chemical, found \"{}\"", text.trim())),
+    };
+    if quantity == 0 {
+        return Err(anyhow!("Quantity of {} has to be positive", chemical));
+    }
+
+    Ok((chemical, quantity))
+}
+
+// Topological order from FUEL to ORE. Fails when a chemical can not be made or the reactions
+// form a cycle.
+fn order(reactions: &HashMap<String, Reaction>) -> anyhow::Result<Vec<String>> {
+    if !reactions.contains_key(FUEL) {
+        return Err(anyhow!("No reaction produces {}", FUEL));
+    }
+
+    // Number of reactions consuming every chemical
+    let mut consumers: HashMap<&str, usize> = HashMap::new();
+    for reaction in reactions.values() {
+        for (input, _) in reaction.inputs.iter() {
+            if input != ORE && !reactions.contains_key(input) {
+                return Err(anyhow!("No reaction produces {}", input));
+            }
+
+            *consumers.entry(input).or_insert(0) += 1;
+        }
+    }
+
+    let mut ready: Vec<&str> = reactions.keys()
+        .map(String::as_str)
+        .filter(|chemical| !consumers.contains_key(chemical))
+        .collect();
+    let mut order = Vec::new();
+
+    while let Some(chemical) = ready.pop() {
+        order.push(chemical.to_string());
+
+        for (input, _) in reactions.get(chemical).map(|reaction| reaction.inputs.as_slice()).unwrap_or(&[]) {
+            let count = consumers.get_mut(input.as_str()).expect("every input has a consumer");
+            *count -= 1;
+
+            if *count == 0 {
+                ready.push(input.as_str());
+            }
+        }
+    }
+
+    let cyclic: Vec<&str> = consumers.iter()
+        .filter(|(chemical, count)| **count > 0 && **chemical != ORE)
+        .map(|(chemical, _)| *chemical)
+        .collect();
+    if !cyclic.is_empty() {
+        return Err(anyhow!("Reactions form a cycle through {}", cyclic.iter().sorted().join(", ")));
+    }
+
+    Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMALL: &str = "\
+10 ORE => 10 A
+1 ORE => 1 B
+7 A, 1 B => 1 C
+7 A, 1 C => 1 D
+7 A, 1 D => 1 E
+7 A, 1 E => 1 FUEL";
+
+    const LARGE: &str = "\
+157 ORE => 5 NZVS
+165 ORE => 6 DCFZ
+44 XJWVT, 5 KHKGT, 1 QDVJ, 29 NZVS, 9 GPVTF, 48 HKGWZ => 1 FUEL
+12 HKGWZ, 1 GPVTF, 8 PSHF => 9 QDVJ
+179 ORE => 7 PSHF
+177 ORE => 5 HKGWZ
+7 DCFZ, 7 PSHF => 2 XJWVT
+165 ORE => 2 GPVTF
+3 DCFZ, 7 NZVS, 5 HKGWZ, 10 PSHF => 8 KHKGT";
+
+    fn error(text: &str) -> String {
+        Factory::new(text).err().expect("reactions are rejected").to_string()
+    }
+
+    #[test]
+    fn ore_for_one_fuel() {
+        assert_eq!(Factory::new(SMALL).unwrap().ore_needed(1).unwrap(), 31);
+        assert_eq!(Factory::new(LARGE).unwrap().ore_needed(1).unwrap(), 13312);
+    }
+
+    #[test]
+    fn fuel_from_the_cargo() {
+        assert_eq!(Factory::new(LARGE).unwrap().max_fuel(CARGO_ORE).unwrap(), 82892753);
+    }
+
+    #[test]
+    fn invalid_reactions_are_rejected() {
+        assert_eq!(error("1 A => 1 B\n1 B => 1 A\n1 B => 1 FUEL"), "Reactions form a cycle through A, B");
+        assert_eq!(error("1 ORE => 1 A\n2 ORE => 1 A\n1 A => 1 FUEL"), "Line 2: A is produced by more than one reaction");
+        assert_eq!(error("0 ORE => 1 FUEL"), "Line 1: Quantity of ORE has to be positive");
+        assert_eq!(error("1 ORE => 0 FUEL"), "Line 1: Quantity of FUEL has to be positive");
+        assert_eq!(error("1 ORE => 1 A"), "No reaction produces FUEL");
+        assert_eq!(error("1 B => 1 FUEL"), "No reaction produces B");
+    }
+}
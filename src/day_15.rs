@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use crate::day_2;
+use crate::intcode::{Event, Machine};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Wall,
+    Open,
+    Oxygen,
+}
+
+type Position = (isize, isize);
+type Area = HashMap<Position, Cell>;
+
+// Movement commands understood by the droid with their offsets. Y axis points down.
+const MOVES: [(isize, (isize, isize)); 4] = [(1, (0, -1)), (2, (0, 1)), (3, (-1, 0)), (4, (1, 0))];
+
+pub fn repair_oxygen_system(filepath: String) -> anyhow::Result<()> {
+    let program = day_2::get_instructions(filepath)?;
+
+    let (area, oxygen) = explore(Machine::new(program))?;
+
+    print!("{}", render(&area));
+
+    let (oxygen, commands) = oxygen.ok_or(anyhow!("Oxygen system was not found"))?;
+    println!("Oxygen system is {} movement commands away", commands);
+
+    // Oxygen spreads one cell per minute, so the fill time is the distance to the furthest cell
+    let fill_time = distances(&area, oxygen).values().max().copied().unwrap_or(0);
+    println!("Area is filled with oxygen after {} minutes", fill_time);
+
+    Ok(())
+}
+
+// Breadth-first search over droid states. Every reached cell keeps a clone of the machine that
+// stands on it, so trying a move never has to walk the droid back.
+// Returns the explored area and where the oxygen system is with its distance from the start
+fn explore(machine: Machine) -> anyhow::Result<(Area, Option<(Position, usize)>)> {
+    let mut area: Area = HashMap::new();
+    let mut oxygen = None;
+    let mut queue: VecDeque<(Position, usize, Machine)> = VecDeque::new();
+
+    area.insert((0, 0), Cell::Open);
+    queue.push_back(((0, 0), 0, machine));
+
+    while let Some((position, distance, machine)) = queue.pop_front() {
+        for (command, offset) in MOVES.iter() {
+            let next = (position.0 + offset.0, position.1 + offset.1);
+            if area.contains_key(&next) {
+                continue;
+            }
+
+            let mut droid = machine.clone();
+            droid.push_input(*command);
+
+            let cell = match droid.run()? {
+                Event::Output(0) => Cell::Wall,
+                Event::Output(1) => Cell::Open,
+                Event::Output(2) => Cell::Oxygen,
+                event => return Err(anyhow!("Unexpected droid response {:?}", event)),
+            };
+
+            area.insert(next, cell);
+
+            if cell == Cell::Oxygen && oxygen.is_none() {
+                oxygen = Some((next, distance + 1));
+            }
+            if cell != Cell::Wall {
+                queue.push_back((next, distance + 1, droid));
+            }
+        }
+    }
+
+    Ok((area, oxygen))
+}
+
+// Length of the shortest path from the start to every reachable cell
+fn distances(area: &Area, start: Position) -> HashMap<Position, usize> {
+    let mut distances = HashMap::new();
+    let mut queue = VecDeque::new();
+
+    distances.insert(start, 0);
+    queue.push_back(start);
+
+    while let Some(position) = queue.pop_front() {
+        let distance = distances[&position];
+
+        for (_, offset) in MOVES.iter() {
+            let next = (position.0 + offset.0, position.1 + offset.1);
+
+            if area.get(&next).is_some_and(|cell| *cell != Cell::Wall) && !distances.contains_key(&next) {
+                distances.insert(next, distance + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    distances
+}
+
+fn render(area: &Area) -> String {
+    let min_x = area.keys().map(|p| p.0).min().unwrap_or(0);
+    let max_x = area.keys().map(|p| p.0).max().unwrap_or(0);
+    let min_y = area.keys().map(|p| p.1).min().unwrap_or(0);
+    let max_y = area.keys().map(|p| p.1).max().unwrap_or(0);
+
+    let mut map = String::new();
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            map.push(match area.get(&(x, y)) {
+                _ if (x, y) == (0, 0) => 'D',
+                Some(Cell::Wall) => '█',
+                Some(Cell::Open) => '.',
+                Some(Cell::Oxygen) => 'O',
+                None => ' ',
+            });
+        }
+        map.push('\n');
+    }
+
+    map
+}
@@ -0,0 +1,97 @@
+use std::fs;
+
+const PHASES: usize = 100;
+const REPETITIONS: usize = 10_000;
+const MESSAGE_LENGTH: usize = 8;
+// Number of leading digits of the signal giving the message offset
+const OFFSET_DIGITS: usize = 7;
+
+pub fn first_digits(filepath: String) -> anyhow::Result<()> {
+    let mut signal = get_signal(filepath)?;
+
+    for _ in 0..PHASES {
+        signal = phase(&signal);
+    }
+
+    println!("First {} digits after {} phases are {}", MESSAGE_LENGTH, PHASES, to_text(&signal)?);
+
+    Ok(())
+}
+
+// In the second half of the signal the pattern is zeros followed by ones only, so every digit is
+// the sum of the digits from it to the end. Only the part from the offset on has to be computed.
+pub fn real_message(filepath: String) -> anyhow::Result<()> {
+    let signal = get_signal(filepath)?;
+    let length = signal.len() * REPETITIONS;
+
+    if signal.len() < OFFSET_DIGITS {
+        return Err(anyhow!("Signal of {} digits is too short to hold the message offset", signal.len()));
+    }
+    let offset = signal[..OFFSET_DIGITS].iter().fold(0, |offset, digit| offset * 10 + *digit as usize);
+
+    if offset < length / 2 {
+        return Err(anyhow!("Message offset {} is in the first half of the {} digit signal, the suffix sum shortcut does not apply", offset, length));
+    }
+    if offset + MESSAGE_LENGTH > length {
+        return Err(anyhow!("Message offset {} is past the end of the {} digit signal", offset, length));
+    }
+
+    let mut tail: Vec<u8> = (offset..length).map(|i| signal[i % signal.len()]).collect();
+
+    for _ in 0..PHASES {
+        let mut sum: u32 = 0;
+
+        for digit in tail.iter_mut().rev() {
+            sum += *digit as u32;
+            *digit = (sum % 10) as u8;
+        }
+    }
+
+    println!("Message at offset {} is {}", offset, to_text(&tail)?);
+
+    Ok(())
+}
+
+fn get_signal(filepath: String) -> anyhow::Result<Vec<u8>> {
+    let mut signal = Vec::new();
+
+    for (i, c) in fs::read_to_string(filepath)?.trim().chars().enumerate() {
+        let digit = c.to_digit(10).ok_or(anyhow!("Signal digit {} is not a digit: {:?}", i, c))?;
+        signal.push(digit as u8);
+    }
+
+    Ok(signal)
+}
+
+// Output digit i sums the input under the pattern 0, 1, 0, -1 with every element repeated i + 1
+// times and the very first element skipped. Prefix sums make every run of ones or minus ones a
+// single subtraction.
+fn phase(signal: &[u8]) -> Vec<u8> {
+    let mut prefix = vec![0i64; signal.len() + 1];
+    for (i, digit) in signal.iter().enumerate() {
+        prefix[i + 1] = prefix[i] + *digit as i64;
+    }
+    let sum = |from: usize, to: usize| prefix[to.min(signal.len())] - prefix[from.min(signal.len())];
+
+    (1..=signal.len())
+        .map(|width| {
+            let mut total = 0;
+            let mut start = width - 1;
+
+            while start < signal.len() {
+                total += sum(start, start + width);
+                total -= sum(start + 2 * width, start + 3 * width);
+                start += 4 * width;
+            }
+
+            (total.abs() % 10) as u8
+        })
+        .collect()
+}
+
+fn to_text(signal: &[u8]) -> anyhow::Result<String> {
+    let digits = signal.get(..MESSAGE_LENGTH)
+        .ok_or(anyhow!("Signal of {} digits is shorter than the message", signal.len()))?;
+
+    Ok(digits.iter().map(|digit| (b'0' + digit) as char).collect())
+}
@@ -0,0 +1,238 @@
+use crate::day_2;
+use crate::intcode::Machine;
+
+// Address which wakes the robot up when set to 2
+const WAKE_UP_ADDRESS: usize = 0;
+const WAKE_UP: isize = 2;
+// Length limit of the main routine and every movement function, without the newline
+const ROUTINE_LIMIT: usize = 20;
+const FUNCTION_NAMES: [&str; 3] = ["A", "B", "C"];
+
+type View = Vec<Vec<u8>>;
+
+pub fn alignment_parameters(filepath: String) -> anyhow::Result<()> {
+    let view = camera_view(day_2::get_instructions(filepath)?)?;
+
+    for row in view.iter() {
+        println!("{}", String::from_utf8_lossy(row));
+    }
+
+    let sum: usize = intersections(&view).iter().map(|(x, y)| x * y).sum();
+    println!("Sum of the alignment parameters is {}", sum);
+
+    Ok(())
+}
+
+pub fn collect_dust(filepath: String) -> anyhow::Result<()> {
+    let program = day_2::get_instructions(filepath)?;
+    let view = camera_view(program.to_vec())?;
+
+    let path = find_path(&view)?;
+    println!("Path: {}", path.join(","));
+
+    let (main, functions) = compress(&path).ok_or(anyhow!("Path can not be split into movement functions"))?;
+    println!("Main routine: {}", main);
+    for (name, function) in FUNCTION_NAMES.iter().zip(functions.iter()) {
+        println!("Function {}: {}", name, function);
+    }
+
+    let mut machine = Machine::new(program);
+    machine.poke(WAKE_UP_ADDRESS, WAKE_UP);
+
+    machine.push_ascii(&format!("{}\n", main));
+    for i in 0..FUNCTION_NAMES.len() {
+        // Unused functions still have to be answered
+        machine.push_ascii(&format!("{}\n", functions.get(i).map(String::as_str).unwrap_or("")));
+    }
+    machine.push_ascii("n\n"); // no continuous video feed
+
+    // Everything but the last output is the ASCII prompt and the camera view
+    let outputs = machine.run_with_input(&[])?;
+    match outputs.last() {
+        Some(dust) if *dust > 127 => println!("Robot collected {} dust", dust),
+        _ => println!("Robot did not report the dust: {}", to_text(&outputs)),
+    }
+
+    Ok(())
+}
+
+fn camera_view(program: Vec<isize>) -> anyhow::Result<View> {
+    let outputs = Machine::new(program).run_with_input(&[])?;
+
+    Ok(to_text(&outputs).lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.as_bytes().to_vec())
+        .collect())
+}
+
+fn to_text(outputs: &[isize]) -> String {
+    outputs.iter().filter(|c| (0..128).contains(*c)).map(|c| *c as u8 as char).collect()
+}
+
+fn is_scaffold(view: &View, x: isize, y: isize) -> bool {
+    if x < 0 || y < 0 {
+        return false;
+    }
+
+    matches!(view.get(y as usize).and_then(|row| row.get(x as usize)), Some(b'#') | Some(b'^') | Some(b'v') | Some(b'<') | Some(b'>'))
+}
+
+// Scaffold cells with scaffold on all four sides
+fn intersections(view: &View) -> Vec<(usize, usize)> {
+    let mut intersections = Vec::new();
+
+    for (y, row) in view.iter().enumerate() {
+        for x in 0..row.len() {
+            let (sx, sy) = (x as isize, y as isize);
+
+            if is_scaffold(view, sx, sy) && is_scaffold(view, sx-1, sy) && is_scaffold(view, sx+1, sy)
+            && is_scaffold(view, sx, sy-1) && is_scaffold(view, sx, sy+1) {
+                intersections.push((x, y));
+            }
+        }
+    }
+
+    intersections
+}
+
+// Walks the scaffold from the robot going straight through intersections. Returns tokens like
+// "R", "8", "L", "10", starting with a distance when the robot already faces along the scaffold.
+// Y axis points down.
+fn find_path(view: &View) -> anyhow::Result<Vec<String>> {
+    let (mut position, mut direction) = view.iter().enumerate()
+        .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, c)| ((x as isize, y as isize), *c)))
+        .find_map(|(position, c)| match c {
+            b'^' => Some((position, (0, -1))),
+            b'v' => Some((position, (0, 1))),
+            b'<' => Some((position, (-1, 0))),
+            b'>' => Some((position, (1, 0))),
+            _ => None,
+        })
+        .ok_or(anyhow!("Robot is not visible on the camera"))?;
+
+    let mut path = Vec::new();
+    let forward = |position: &mut (isize, isize), direction: (isize, isize)| {
+        let mut steps = 0;
+        while is_scaffold(view, position.0 + direction.0, position.1 + direction.1) {
+            *position = (position.0 + direction.0, position.1 + direction.1);
+            steps += 1;
+        }
+
+        steps
+    };
+
+    let steps = forward(&mut position, direction);
+    if steps > 0 {
+        path.push(steps.to_string());
+    }
+
+    loop {
+        let left = (direction.1, -direction.0);
+        let right = (-direction.1, direction.0);
+
+        direction = if is_scaffold(view, position.0 + left.0, position.1 + left.1) {
+            path.push("L".to_string());
+            left
+        } else if is_scaffold(view, position.0 + right.0, position.1 + right.1) {
+            path.push("R".to_string());
+            right
+        } else {
+            break; // end of the scaffold
+        };
+
+        path.push(forward(&mut position, direction).to_string());
+    }
+
+    if path.is_empty() {
+        return Err(anyhow!("Robot at ({}, {}) has no scaffold to move along", position.0, position.1));
+    }
+
+    Ok(path)
+}
+
+// Splits the path into a main routine calling at most three movement functions. Turns are never
+// separated from the distance that follows them, a leading distance is a move of its own.
+fn compress(path: &[String]) -> Option<(String, Vec<String>)> {
+    let straight = path.first().is_some_and(|token| token.parse::<usize>().is_ok());
+    let (ahead, turns) = path.split_at(if straight { 1 } else { 0 });
+    let moves: Vec<String> = ahead.iter().cloned().chain(turns.chunks(2).map(|pair| pair.join(","))).collect();
+    let mut functions: Vec<&[String]> = Vec::new();
+    let mut calls: Vec<usize> = Vec::new();
+
+    if !search(&moves, 0, &mut functions, &mut calls) {
+        return None;
+    }
+
+    let main = calls.iter().map(|i| FUNCTION_NAMES[*i]).collect::<Vec<&str>>().join(",");
+    let functions = functions.iter().map(|function| function.join(",")).collect();
+
+    Some((main, functions))
+}
+
+fn search<'a>(moves: &'a [String], position: usize, functions: &mut Vec<&'a [String]>, calls: &mut Vec<usize>) -> bool {
+    if position == moves.len() {
+        return true;
+    }
+    // Every call takes a letter and a comma
+    if (calls.len() + 1) * 2 - 1 > ROUTINE_LIMIT {
+        return false;
+    }
+
+    // Reuse a function matching the rest of the path
+    for i in 0..functions.len() {
+        let function = functions[i];
+
+        if moves[position..].starts_with(function) {
+            calls.push(i);
+            if search(moves, position + function.len(), functions, calls) {
+                return true;
+            }
+            calls.pop();
+        }
+    }
+
+    // Define a new function starting here, longest first
+    if functions.len() < FUNCTION_NAMES.len() {
+        let mut end = position;
+        while end < moves.len() && moves[position..=end].join(",").len() <= ROUTINE_LIMIT {
+            end += 1;
+        }
+
+        for end in (position+1..=end).rev() {
+            functions.push(&moves[position..end]);
+            calls.push(functions.len() - 1);
+            if search(moves, end, functions, calls) {
+                return true;
+            }
+            calls.pop();
+            functions.pop();
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(text: &str) -> View {
+        text.lines().map(|line| line.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn robot_facing_along_the_scaffold_moves_first() {
+        let path = find_path(&view(">####\n....#\n....#")).unwrap();
+        assert_eq!(path, vec!["4", "R", "2"]);
+
+        let (main, functions) = compress(&path).unwrap();
+        assert_eq!((main.as_str(), functions), ("A", vec!["4,R,2".to_string()]));
+    }
+
+    #[test]
+    fn paths_with_and_without_a_first_turn() {
+        assert_eq!(find_path(&view("^....\n#####")).unwrap_err().to_string(), "Robot at (0, 0) has no scaffold to move along");
+        assert_eq!(find_path(&view("v....\n#....\n###..")).unwrap(), vec!["2", "L", "2"]);
+        assert_eq!(find_path(&view("^###")).unwrap(), vec!["R", "3"]);
+    }
+}
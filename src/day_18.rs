@@ -0,0 +1,176 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fs;
+
+const KEY_COUNT: usize = 26;
+const MAX_ROBOTS: usize = 4;
+// Replaces the 3x3 area around the single entrance in the second part
+const SPLIT_ENTRANCE: [&[u8; 3]; 3] = [b"@#@", b"###", b"@#@"];
+
+type Grid = Vec<Vec<u8>>;
+
+// Places of interest are numbered with keys first, so a key number is also its bit in a key set
+// and robot r starts at KEY_COUNT + r
+#[derive(Debug, Clone, Copy)]
+struct Path {
+    to: usize, // key number
+    length: usize,
+    doors: u32, // keys needed to open the doors on the way
+}
+
+// Where every robot stands and which keys are collected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct State {
+    robots: [u8; MAX_ROBOTS],
+    keys: u32,
+}
+
+pub fn collect_keys(filepath: String) -> anyhow::Result<()> {
+    let grid = get_grid(filepath)?;
+
+    println!("Shortest path collecting every key takes {} steps", shortest_path(&grid)?);
+
+    Ok(())
+}
+
+pub fn collect_keys_with_robots(filepath: String) -> anyhow::Result<()> {
+    let mut grid = get_grid(filepath)?;
+
+    // Maps already split into vaults are used as they are
+    let entrances = find(&grid, b'@');
+    if entrances.len() == 1 {
+        let (x, y) = entrances[0];
+        if x == 0 || y == 0 || y + 1 >= grid.len() || x + 1 >= grid[y].len() {
+            return Err(anyhow!("Entrance at ({}, {}) is on the edge of the map", x, y));
+        }
+
+        for (dy, row) in SPLIT_ENTRANCE.iter().enumerate() {
+            for (dx, cell) in row.iter().enumerate() {
+                grid[y + dy - 1][x + dx - 1] = *cell;
+            }
+        }
+    }
+
+    println!("Shortest path of the robots collecting every key takes {} steps", shortest_path(&grid)?);
+
+    Ok(())
+}
+
+fn get_grid(filepath: String) -> anyhow::Result<Grid> {
+    Ok(fs::read_to_string(filepath)?.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.as_bytes().to_vec())
+        .collect())
+}
+
+fn find(grid: &Grid, cell: u8) -> Vec<(usize, usize)> {
+    grid.iter().enumerate()
+        .flat_map(|(y, row)| row.iter().enumerate().filter(move |(_, c)| **c == cell).map(move |(x, _)| (x, y)))
+        .collect()
+}
+
+// Dijkstra over states, moving one robot at a time straight to a key it can reach
+fn shortest_path(grid: &Grid) -> anyhow::Result<usize> {
+    let entrances = find(grid, b'@');
+    if entrances.is_empty() || entrances.len() > MAX_ROBOTS {
+        return Err(anyhow!("Map has {} entrances, between 1 and {} are supported", entrances.len(), MAX_ROBOTS));
+    }
+
+    let mut places: HashMap<usize, (usize, usize)> = HashMap::new();
+    let mut all_keys: u32 = 0;
+    for (key, position) in (b'a'..=b'z').enumerate().flat_map(|(key, c)| find(grid, c).into_iter().map(move |p| (key, p))) {
+        if places.insert(key, position).is_some() {
+            return Err(anyhow!("Key {} appears more than once", (b'a' + key as u8) as char));
+        }
+        all_keys |= 1 << key;
+    }
+    for (robot, position) in entrances.iter().enumerate() {
+        places.insert(KEY_COUNT + robot, *position);
+    }
+
+    let paths: HashMap<usize, Vec<Path>> = places.iter()
+        .map(|(place, position)| (*place, paths_to_keys(grid, *position)))
+        .collect();
+
+    let mut start = State { robots: [0; MAX_ROBOTS], keys: 0 };
+    for robot in 0..entrances.len() {
+        start.robots[robot] = (KEY_COUNT + robot) as u8;
+    }
+
+    let mut distances: HashMap<State, usize> = HashMap::new();
+    let mut queue = BinaryHeap::new();
+    distances.insert(start, 0);
+    queue.push(Reverse((0, start)));
+
+    while let Some(Reverse((distance, state))) = queue.pop() {
+        if state.keys == all_keys {
+            return Ok(distance);
+        }
+        if distances.get(&state).is_some_and(|best| *best < distance) {
+            continue;
+        }
+
+        for robot in 0..entrances.len() {
+            let reachable = paths[&(state.robots[robot] as usize)].iter()
+                .filter(|path| state.keys & (1 << path.to) == 0 && path.doors & !state.keys == 0);
+
+            for path in reachable {
+                let mut next = state;
+                next.robots[robot] = path.to as u8;
+                next.keys |= 1 << path.to;
+
+                let length = distance + path.length;
+                if distances.get(&next).is_none_or(|best| length < *best) {
+                    distances.insert(next, length);
+                    queue.push(Reverse((length, next)));
+                }
+            }
+        }
+    }
+
+    Err(anyhow!("Not every key can be collected"))
+}
+
+// Breadth-first search from the position to every key, remembering the doors on the way. Paths
+// run through other keys, picking them up on the way is left to the state search.
+fn paths_to_keys(grid: &Grid, from: (usize, usize)) -> Vec<Path> {
+    let mut paths = Vec::new();
+    let mut seen = vec![vec![false; grid.iter().map(Vec::len).max().unwrap_or(0)]; grid.len()];
+    let mut queue = VecDeque::new();
+
+    seen[from.1][from.0] = true;
+    queue.push_back((from, 0, 0u32));
+
+    while let Some(((x, y), length, doors)) = queue.pop_front() {
+        for (dx, dy) in [(0, -1), (0, 1), (-1, 0), (1, 0)].iter() {
+            let (nx, ny) = (x as isize + dx, y as isize + dy);
+            if nx < 0 || ny < 0 {
+                continue;
+            }
+            let (nx, ny) = (nx as usize, ny as usize);
+
+            let cell = match grid.get(ny).and_then(|row| row.get(nx)) {
+                Some(b'#') | None => continue,
+                Some(cell) => *cell,
+            };
+            if seen[ny][nx] {
+                continue;
+            }
+            seen[ny][nx] = true;
+
+            let mut doors = doors;
+            match cell {
+                b'a'..=b'z' => paths.push(Path { to: (cell - b'a') as usize, length: length + 1, doors }),
+                b'A'..=b'Z' => doors |= 1 << (cell - b'A'),
+                _ => {},
+            }
+
+            queue.push_back(((nx, ny), length + 1, doors));
+        }
+    }
+
+    paths
+}
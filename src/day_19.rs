@@ -0,0 +1,74 @@
+use crate::day_2;
+use crate::intcode::Machine;
+
+const SCAN_SIZE: isize = 50;
+const SHIP_SIZE: isize = 100;
+// Rows further than this from the emitter are not searched for the ship
+const MAX_DISTANCE: isize = 100_000;
+// Beam never spreads further right than this many times the row number
+const MAX_SLOPE: isize = 10;
+
+pub fn count_affected_points(filepath: String) -> anyhow::Result<()> {
+    let program = day_2::get_instructions(filepath)?;
+
+    let mut count = 0;
+    for y in 0..SCAN_SIZE {
+        let row = (0..SCAN_SIZE).map(|x| is_pulled(&program, x, y)).collect::<anyhow::Result<Vec<bool>>>()?;
+
+        count += row.iter().filter(|pulled| **pulled).count();
+        println!("{}", row.iter().map(|pulled| if *pulled { '#' } else { '.' }).collect::<String>());
+    }
+
+    println!("Beam affects {} points in the {}x{} area", count, SCAN_SIZE, SCAN_SIZE);
+
+    Ok(())
+}
+
+pub fn fit_ship(filepath: String) -> anyhow::Result<()> {
+    let program = day_2::get_instructions(filepath)?;
+
+    let (x, y) = find_square(SHIP_SIZE, |x, y| is_pulled(&program, x, y))?
+        .ok_or(anyhow!("Ship does not fit in the beam within {} rows", MAX_DISTANCE))?;
+
+    println!("Closest point of the ship is at ({}, {}), answer is {}", x, y, x * 10000 + y);
+
+    Ok(())
+}
+
+// Every query needs a fresh machine, the drone program halts after one answer
+fn is_pulled(program: &[isize], x: isize, y: isize) -> anyhow::Result<bool> {
+    let outputs = Machine::new(program.to_vec()).run_with_input(&[x, y])?;
+
+    match outputs.first() {
+        Some(state) => Ok(*state == 1),
+        None => Err(anyhow!("Drone did not report ({}, {})", x, y)),
+    }
+}
+
+// Top left corner of the closest square of the given size inside the beam. Walks down the left
+// edge of the beam: the square fits when its bottom left corner is on the edge and the top right
+// corner is still pulled.
+fn find_square<F>(size: isize, is_pulled: F) -> anyhow::Result<Option<(isize, isize)>>
+where F: Fn(isize, isize) -> anyhow::Result<bool> {
+    let mut left = 0;
+
+    for y in size-1..MAX_DISTANCE {
+        // Beam moves right further from the emitter, so the left edge never moves back
+        let mut x = left;
+        while x <= y * MAX_SLOPE && !is_pulled(x, y)? {
+            x += 1;
+        }
+
+        // Rows close to the emitter can miss the beam entirely
+        if x > y * MAX_SLOPE {
+            continue;
+        }
+        left = x;
+
+        if is_pulled(x + size - 1, y - (size - 1))? {
+            return Ok(Some((x, y - (size - 1))));
+        }
+    }
+
+    Ok(None)
+}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::fs;
+
+const START: &str = "AA";
+const END: &str = "ZZ";
+
+type Position = (usize, usize);
+
+struct Maze {
+    grid: Vec<Vec<u8>>,
+    // Open tile next to a portal label leads to the tile next to the other label of the pair,
+    // together with the level change: inner portals go one level down, outer portals one up
+    portals: HashMap<Position, (Position, isize)>,
+    start: Position,
+    end: Position,
+}
+
+pub fn shortest_path(filepath: String) -> anyhow::Result<()> {
+    let maze = Maze::new(&fs::read_to_string(filepath)?)?;
+
+    let steps = maze.search(None).ok_or(anyhow!("{} can not be reached from {}", END, START))?;
+    println!("Shortest path from {} to {} takes {} steps", START, END, steps);
+
+    Ok(())
+}
+
+pub fn shortest_recursive_path(filepath: String, max_depth: usize) -> anyhow::Result<()> {
+    let maze = Maze::new(&fs::read_to_string(filepath)?)?;
+
+    let steps = maze.search(Some(max_depth))
+        .ok_or(anyhow!("{} can not be reached from {} going at most {} levels deep", END, START, max_depth))?;
+    println!("Shortest path from {} to {} through the levels takes {} steps", START, END, steps);
+
+    Ok(())
+}
+
+impl Maze {
+    fn new(text: &str) -> anyhow::Result<Maze> {
+        // Leading spaces position the maze, so lines are not trimmed
+        let lines: Vec<&[u8]> = text.lines().map(str::as_bytes).filter(|line| !line.is_empty()).collect();
+        let width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+        let grid: Vec<Vec<u8>> = lines.iter()
+            .map(|line| {
+                let mut row = line.to_vec();
+                row.resize(width, b' ');
+                row
+            })
+            .collect();
+
+        let get = |x: isize, y: isize| -> u8 {
+            if x < 0 || y < 0 {
+                return b' ';
+            }
+            grid.get(y as usize).and_then(|row| row.get(x as usize)).copied().unwrap_or(b' ')
+        };
+
+        // Labels read left to right or top to bottom and touch the open tile with either letter
+        let mut labels: HashMap<String, Vec<Position>> = HashMap::new();
+        for y in 0..grid.len() as isize {
+            for x in 0..width as isize {
+                let first = get(x, y);
+                if !first.is_ascii_uppercase() {
+                    continue;
+                }
+
+                for (dx, dy) in [(1, 0), (0, 1)].iter() {
+                    let second = get(x + dx, y + dy);
+                    if !second.is_ascii_uppercase() {
+                        continue;
+                    }
+
+                    let tile = if get(x - dx, y - dy) == b'.' {
+                        (x - dx, y - dy)
+                    } else if get(x + 2 * dx, y + 2 * dy) == b'.' {
+                        (x + 2 * dx, y + 2 * dy)
+                    } else {
+                        return Err(anyhow!("Label at ({}, {}) is not next to an open tile", x, y));
+                    };
+
+                    let label = format!("{}{}", first as char, second as char);
+                    labels.entry(label).or_default().push((tile.0 as usize, tile.1 as usize));
+                }
+            }
+        }
+
+        let single = |label: &str| -> anyhow::Result<Position> {
+            match labels.get(label).map(Vec::as_slice) {
+                Some([position]) => Ok(*position),
+                _ => Err(anyhow!("Maze needs exactly one {} tile", label)),
+            }
+        };
+        let start = single(START)?;
+        let end = single(END)?;
+
+        let is_outer = |(x, y): Position| x <= 2 || y <= 2 || x + 3 >= width || y + 3 >= grid.len();
+
+        let mut portals = HashMap::new();
+        for (label, tiles) in labels.iter().filter(|(label, _)| *label != START && *label != END) {
+            let (a, b) = match tiles.as_slice() {
+                [a, b] => (*a, *b),
+                _ => return Err(anyhow!("Portal {} has {} ends instead of 2", label, tiles.len())),
+            };
+            if is_outer(a) == is_outer(b) {
+                return Err(anyhow!("Portal {} has to connect the inner and the outer edge", label));
+            }
+
+            portals.insert(a, (b, if is_outer(a) { -1 } else { 1 }));
+            portals.insert(b, (a, if is_outer(b) { -1 } else { 1 }));
+        }
+
+        Ok(Maze { grid, portals, start, end })
+    }
+
+    // Breadth-first search over tiles and levels. Without a depth limit every portal keeps the
+    // level, otherwise outer portals are walls on the outermost level and no path goes deeper
+    // than the limit.
+    fn search(&self, max_depth: Option<usize>) -> Option<usize> {
+        let mut seen: HashSet<(Position, usize)> = HashSet::new();
+        let mut queue = VecDeque::new();
+
+        seen.insert((self.start, 0));
+        queue.push_back((self.start, 0, 0));
+
+        while let Some((position, depth, steps)) = queue.pop_front() {
+            if position == self.end && depth == 0 {
+                return Some(steps);
+            }
+
+            let (x, y) = position;
+            let mut next: Vec<(Position, usize)> = [(x, y.wrapping_sub(1)), (x, y + 1), (x.wrapping_sub(1), y), (x + 1, y)].iter()
+                .filter(|(nx, ny)| self.grid.get(*ny).and_then(|row| row.get(*nx)) == Some(&b'.'))
+                .map(|neighbour| (*neighbour, depth))
+                .collect();
+
+            if let Some((exit, change)) = self.portals.get(&position) {
+                match max_depth {
+                    None => next.push((*exit, depth)),
+                    Some(max_depth) => {
+                        let level = depth as isize + change;
+                        if level >= 0 && level as usize <= max_depth {
+                            next.push((*exit, level as usize));
+                        }
+                    },
+                }
+            }
+
+            for state in next {
+                if seen.insert(state) {
+                    queue.push_back((state.0, state.1, steps + 1));
+                }
+            }
+        }
+
+        None
+    }
+}
@@ -0,0 +1,363 @@
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+
+use crate::day_2;
+use crate::intcode::Machine;
+
+// Springdroid memory holds at most this many instructions
+const INSTRUCTION_LIMIT: usize = 15;
+// Jump takes the droid this many tiles ahead
+const JUMP_LENGTH: usize = 4;
+// Searcher gives up after finding this many scripts computing different things
+const SEARCH_LIMIT: usize = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Walk,
+    Run,
+}
+
+impl Mode {
+    // Number of hull sensors, A is the closest
+    fn sensors(self) -> usize {
+        match self {
+            Mode::Walk => 4,
+            Mode::Run => 9,
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mode::Walk => write!(f, "WALK"),
+            Mode::Run => write!(f, "RUN"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    And,
+    Or,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Instruction {
+    operation: Operation,
+    x: char, // read register
+    y: char, // read and written register, either T or J
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let operation = match self.operation {
+            Operation::And => "AND",
+            Operation::Or => "OR",
+            Operation::Not => "NOT",
+        };
+
+        write!(f, "{} {} {}", operation, self.x, self.y)
+    }
+}
+
+struct Script {
+    instructions: Vec<Instruction>,
+    mode: Mode,
+}
+
+impl Script {
+    // Text the springdroid expects, one instruction per line
+    fn to_ascii(&self) -> String {
+        let mut text = String::new();
+
+        for instruction in self.instructions.iter() {
+            text.push_str(&format!("{}\n", instruction));
+        }
+        text.push_str(&format!("{}\n", self.mode));
+
+        text
+    }
+}
+
+enum Outcome {
+    Damage(isize),
+    // Droid fell into space, the ASCII animation of the fall and the hull it fell on
+    Fell(String, Vec<bool>),
+}
+
+pub fn run_script(filepath: String, script_path: String) -> anyhow::Result<()> {
+    let program = day_2::get_instructions(filepath)?;
+    let script = parse_script(&fs::read_to_string(script_path)?)?;
+
+    match survey(&program, &script)? {
+        Outcome::Damage(damage) => println!("Amount of hull damage is {}", damage),
+        Outcome::Fell(animation, _) => print!("{}", animation),
+    }
+
+    Ok(())
+}
+
+pub fn search_script(filepath: String, mode: Mode) -> anyhow::Result<()> {
+    let program = day_2::get_instructions(filepath)?;
+
+    let (script, damage, tried) = search(mode, |script| survey(&program, script))?;
+
+    print!("{}", script.to_ascii());
+    println!("Amount of hull damage is {}, found after running {} scripts", damage, tried);
+
+    Ok(())
+}
+
+// Tries scripts from the shortest ones. Every fall teaches the searcher a new hull, and scripts
+// are checked against all known hulls before the springdroid has to run them. Returns the
+// script, the hull damage and how many scripts the springdroid ran.
+fn search<F>(mode: Mode, mut survey: F) -> anyhow::Result<(Script, isize, usize)>
+where F: FnMut(&Script) -> anyhow::Result<Outcome> {
+    let sensors = mode.sensors();
+
+    let mut hulls: Vec<Vec<bool>> = Vec::new();
+    let mut tried = 0;
+
+    // Scripts are grouped by what they compute, only the first script computing the same
+    // registers is kept. Initially both registers are false.
+    let empty = Table::new(sensors);
+    let mut seen: HashSet<(Table, Table)> = HashSet::new();
+    let mut queue: VecDeque<(Table, Table, Vec<Instruction>)> = VecDeque::new();
+
+    seen.insert((empty.clone(), empty.clone()));
+    queue.push_back((empty.clone(), empty, Vec::new()));
+
+    while let Some((t, j, instructions)) = queue.pop_front() {
+        if !instructions.is_empty() && hulls.iter().all(|hull| survives(hull, &j, sensors)) {
+            let script = Script { instructions: instructions.to_vec(), mode };
+            tried += 1;
+
+            match survey(&script)? {
+                Outcome::Damage(damage) => { return Ok((script, damage, tried)); },
+                Outcome::Fell(_, hull) => {
+                    debug!("Script fell on {:?}", hull);
+                    hulls.push(hull);
+                },
+            }
+        }
+
+        if instructions.len() == INSTRUCTION_LIMIT || seen.len() >= SEARCH_LIMIT {
+            continue;
+        }
+
+        for instruction in all_instructions(mode) {
+            let x = match instruction.x {
+                'T' => t.clone(),
+                'J' => j.clone(),
+                sensor => Table::sensor(sensors, sensor as usize - 'A' as usize),
+            };
+            let y = if instruction.y == 'T' { &t } else { &j };
+
+            let result = match instruction.operation {
+                Operation::And => x.and(y),
+                Operation::Or => x.or(y),
+                Operation::Not => x.not(),
+            };
+            let (next_t, next_j) = if instruction.y == 'T' { (result, j.clone()) } else { (t.clone(), result) };
+
+            if seen.insert((next_t.clone(), next_j.clone())) {
+                let mut next = instructions.to_vec();
+                next.push(instruction);
+                queue.push_back((next_t, next_j, next));
+            }
+        }
+    }
+
+    if seen.len() >= SEARCH_LIMIT {
+        return Err(anyhow!("Search gave up after {} distinct scripts, {} were run", SEARCH_LIMIT, tried));
+    }
+
+    Err(anyhow!("No script of up to {} instructions survives, {} were run", INSTRUCTION_LIMIT, tried))
+}
+
+fn parse_script(text: &str) -> anyhow::Result<Script> {
+    let lines: Vec<(usize, &str)> = text.lines().enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .collect();
+
+    let (last_number, last) = lines.last().ok_or(anyhow!("Script is empty"))?;
+    let mode = match *last {
+        "WALK" => Mode::Walk,
+        "RUN" => Mode::Run,
+        _ => return Err(anyhow!("Line {}: script has to end with WALK or RUN", last_number)),
+    };
+
+    let mut errors = Vec::new();
+    let mut instructions = Vec::new();
+
+    for (number, line) in lines[..lines.len()-1].iter() {
+        match parse_instruction(line, mode) {
+            Ok(instruction) => instructions.push(instruction),
+            Err(error) => errors.push(format!("Line {}: {}", number, error)),
+        }
+    }
+
+    if instructions.len() > INSTRUCTION_LIMIT {
+        errors.push(format!("Script has {} instructions, springdroid fits only {}", instructions.len(), INSTRUCTION_LIMIT));
+    }
+
+    if !errors.is_empty() {
+        return Err(anyhow!("Invalid script:\n{}", errors.join("\n")));
+    }
+
+    Ok(Script { instructions, mode })
+}
+
+fn parse_instruction(line: &str, mode: Mode) -> anyhow::Result<Instruction> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+
+    let operation = match words.first() {
+        Some(&"AND") => Operation::And,
+        Some(&"OR") => Operation::Or,
+        Some(&"NOT") => Operation::Not,
+        Some(&"WALK") | Some(&"RUN") => return Err(anyhow!("{} has to be the last line", line)),
+        _ => return Err(anyhow!("Unknown instruction {}", line)),
+    };
+
+    if words.len() != 3 {
+        return Err(anyhow!("Instruction takes two registers"));
+    }
+
+    let x = register(words[1])?;
+    let y = register(words[2])?;
+
+    if !readable_registers(mode).contains(&x) {
+        return Err(anyhow!("Register {} can not be read in {} mode", x, mode));
+    }
+    if y != 'T' && y != 'J' {
+        return Err(anyhow!("Register {} can not be written, only T and J can", y));
+    }
+
+    Ok(Instruction { operation, x, y })
+}
+
+fn register(word: &str) -> anyhow::Result<char> {
+    let mut chars = word.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(register), None) if readable_registers(Mode::Run).contains(&register) => Ok(register),
+        _ => Err(anyhow!("Unknown register {}", word)),
+    }
+}
+
+fn readable_registers(mode: Mode) -> Vec<char> {
+    let mut registers: Vec<char> = ('A'..='I').take(mode.sensors()).collect();
+    registers.push('T');
+    registers.push('J');
+
+    registers
+}
+
+fn all_instructions(mode: Mode) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+
+    for operation in [Operation::And, Operation::Or, Operation::Not].iter() {
+        for x in readable_registers(mode) {
+            for y in ['T', 'J'].iter() {
+                instructions.push(Instruction { operation: *operation, x, y: *y });
+            }
+        }
+    }
+
+    instructions
+}
+
+fn survey(program: &[isize], script: &Script) -> anyhow::Result<Outcome> {
+    let mut machine = Machine::new(program.to_vec());
+    machine.push_ascii(&script.to_ascii());
+
+    let outputs = machine.run_with_input(&[])?;
+
+    if let Some(damage) = outputs.last().filter(|value| **value > 127) {
+        return Ok(Outcome::Damage(*damage));
+    }
+
+    let animation: String = outputs.iter().map(|c| *c as u8 as char).collect();
+    // Hull is the only line of the animation made of ground and holes
+    let hull = animation.lines()
+        .find(|line| line.contains('#') && line.chars().all(|c| c == '#' || c == '.'))
+        .ok_or(anyhow!("Springdroid did not report the damage nor the fall:\n{}", animation))?
+        .chars()
+        .map(|c| c == '#')
+        .collect();
+
+    Ok(Outcome::Fell(animation, hull))
+}
+
+// Walks the hull locally: the droid stands on the first tile and everything past the end of the
+// hull is ground
+fn survives(hull: &[bool], jump: &Table, sensors: usize) -> bool {
+    let ground = |position: usize| hull.get(position).copied().unwrap_or(true);
+    let mut position = 0;
+
+    while position < hull.len() {
+        if !ground(position) {
+            return false;
+        }
+
+        let reading = (0..sensors).fold(0, |reading, k| reading | (ground(position + 1 + k) as usize) << k);
+        position += if jump.get(reading) { JUMP_LENGTH } else { 1 };
+    }
+
+    true
+}
+
+// Truth table of a register over every combination of sensor readings. Bit k of a reading is
+// the sensor k tiles ahead, counted from 0.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Table {
+    bits: Vec<u64>,
+    size: usize, // number of readings
+}
+
+impl Table {
+    fn new(sensors: usize) -> Table {
+        let size: usize = 1 << sensors;
+
+        Table { bits: vec![0; size.div_ceil(64)], size }
+    }
+
+    fn sensor(sensors: usize, k: usize) -> Table {
+        let mut table = Table::new(sensors);
+
+        for reading in (0..table.size).filter(|reading| reading & (1 << k) != 0) {
+            table.bits[reading / 64] |= 1 << (reading % 64);
+        }
+
+        table
+    }
+
+    fn get(&self, reading: usize) -> bool {
+        self.bits[reading / 64] & (1 << (reading % 64)) != 0
+    }
+
+    fn and(&self, other: &Table) -> Table {
+        Table { bits: self.bits.iter().zip(other.bits.iter()).map(|(a, b)| a & b).collect(), size: self.size }
+    }
+
+    fn or(&self, other: &Table) -> Table {
+        Table { bits: self.bits.iter().zip(other.bits.iter()).map(|(a, b)| a | b).collect(), size: self.size }
+    }
+
+    fn not(&self) -> Table {
+        let mut table = Table { bits: self.bits.iter().map(|a| !a).collect(), size: self.size };
+
+        // Clear bits past the last reading so equal tables compare equal
+        if !self.size.is_multiple_of(64) {
+            let last = table.bits.len() - 1;
+            table.bits[last] &= (1 << (self.size % 64)) - 1;
+        }
+
+        table
+    }
+}
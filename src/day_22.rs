@@ -0,0 +1,131 @@
+use std::fs;
+
+const DECK_SIZE: i128 = 10007;
+const CARD: i128 = 2019;
+const HUGE_DECK_SIZE: i128 = 119_315_717_514_047;
+const SHUFFLES: u64 = 101_741_582_076_661;
+const POSITION: i128 = 2020;
+
+// Every technique moves the card at position x to position a * x + b modulo the deck size, and so
+// does any sequence of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Shuffle {
+    a: i128,
+    b: i128,
+    size: i128,
+}
+
+pub fn card_position(filepath: String) -> anyhow::Result<()> {
+    let shuffle = get_shuffle(filepath, DECK_SIZE)?;
+
+    println!("Card {} ends at position {}", CARD, shuffle.apply(CARD));
+
+    Ok(())
+}
+
+pub fn card_at_position(filepath: String) -> anyhow::Result<()> {
+    let shuffle = get_shuffle(filepath, HUGE_DECK_SIZE)?.repeat(SHUFFLES).inverse()?;
+
+    println!("Card {} ends at position {} after {} shuffles of {} cards", shuffle.apply(POSITION), POSITION, SHUFFLES, HUGE_DECK_SIZE);
+
+    Ok(())
+}
+
+fn get_shuffle(filepath: String, size: i128) -> anyhow::Result<Shuffle> {
+    let mut shuffle = Shuffle::identity(size);
+
+    for (i, line) in fs::read_to_string(filepath)?.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+        let technique = parse_technique(line.trim(), size).map_err(|error| anyhow!("Line {}: {}", i + 1, error))?;
+        shuffle = shuffle.then(&technique);
+    }
+
+    Ok(shuffle)
+}
+
+fn parse_technique(line: &str, size: i128) -> anyhow::Result<Shuffle> {
+    if line == "deal into new stack" {
+        return Ok(Shuffle::new(-1, -1, size));
+    }
+
+    if let Some(n) = line.strip_prefix("cut ") {
+        return Ok(Shuffle::new(1, -n.parse::<i128>()?, size));
+    }
+
+    if let Some(n) = line.strip_prefix("deal with increment ") {
+        let n = n.parse::<i128>()?;
+        // Two cards would land on the same position otherwise
+        if n <= 0 || gcd(n, size) != 1 {
+            return Err(anyhow!("Increment {} does not deal {} cards", n, size));
+        }
+
+        return Ok(Shuffle::new(n, 0, size));
+    }
+
+    Err(anyhow!("Unknown technique \"{}\"", line))
+}
+
+impl Shuffle {
+    fn new(a: i128, b: i128, size: i128) -> Shuffle {
+        Shuffle { a: a.rem_euclid(size), b: b.rem_euclid(size), size }
+    }
+
+    fn identity(size: i128) -> Shuffle {
+        Shuffle::new(1, 0, size)
+    }
+
+    fn apply(&self, position: i128) -> i128 {
+        (self.a * position + self.b).rem_euclid(self.size)
+    }
+
+    // This shuffle followed by the other one. Both factors are below the deck size, so products
+    // fit in 128 bits for decks up to 63 bits.
+    fn then(&self, other: &Shuffle) -> Shuffle {
+        Shuffle::new(other.a * self.a, other.a * self.b + other.b, self.size)
+    }
+
+    // Exponentiation by squaring
+    fn repeat(&self, mut times: u64) -> Shuffle {
+        let mut result = Shuffle::identity(self.size);
+        let mut power = *self;
+
+        while times > 0 {
+            if times & 1 == 1 {
+                result = result.then(&power);
+            }
+            power = power.then(&power);
+            times >>= 1;
+        }
+
+        result
+    }
+
+    // Maps a final position back to the position the card started at
+    fn inverse(&self) -> anyhow::Result<Shuffle> {
+        let a = modular_inverse(self.a, self.size)
+            .ok_or(anyhow!("Shuffle multiplying by {} can not be undone in a deck of {}", self.a, self.size))?;
+
+        Ok(Shuffle::new(a, -a * self.b, self.size))
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 { a.abs() } else { gcd(b, a % b) }
+}
+
+// Extended Euclidean algorithm, None when the value shares a factor with the modulus
+fn modular_inverse(value: i128, modulus: i128) -> Option<i128> {
+    let (mut old_r, mut r) = (value.rem_euclid(modulus), modulus);
+    let (mut old_s, mut s) = (1, 0);
+
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_s, s) = (s, old_s - quotient * s);
+    }
+
+    if old_r != 1 {
+        return None;
+    }
+
+    Some(old_s.rem_euclid(modulus))
+}
@@ -0,0 +1,161 @@
+use std::collections::HashSet;
+use std::fs;
+
+const SIZE: usize = 5;
+const CENTRE: usize = SIZE * SIZE / 2;
+
+// Bit y * SIZE + x is set when the tile has a bug. The biodiversity rating is the layout itself.
+type Layout = u32;
+
+pub fn first_repeated_layout(filepath: String) -> anyhow::Result<()> {
+    let mut layout = get_layout(filepath)?;
+    let mut seen = HashSet::new();
+    let adjacency: Vec<Vec<usize>> = (0..SIZE * SIZE).map(neighbours).collect();
+
+    while seen.insert(layout) {
+        layout = (0..SIZE * SIZE)
+            .filter(|tile| lives(layout & (1 << tile) != 0, adjacency[*tile].iter().filter(|n| layout & (1 << *n) != 0).count()))
+            .fold(0, |next, tile| next | (1 << tile));
+    }
+
+    print!("{}", render(layout));
+    println!("Biodiversity rating of the first repeated layout is {}", layout);
+
+    Ok(())
+}
+
+// The centre tile of every level holds the next level down, the initial layout is level 0 and
+// every other level starts empty
+pub fn recursive_bugs(filepath: String, minutes: usize) -> anyhow::Result<()> {
+    let initial = get_layout(filepath)?;
+    if initial & (1 << CENTRE) != 0 {
+        return Err(anyhow!("Centre tile holds the recursive grid and can not have a bug"));
+    }
+
+    // Levels from the outermost, bugs spread at most one level further out and in per minute
+    let mut levels = vec![initial];
+    let adjacency: Vec<Vec<(isize, usize)>> = (0..SIZE * SIZE).map(recursive_neighbours).collect();
+
+    for _ in 0..minutes {
+        levels.insert(0, 0);
+        levels.push(0);
+
+        let get = |level: isize, tile: usize| {
+            level >= 0 && (level as usize) < levels.len() && levels[level as usize] & (1 << tile) != 0
+        };
+
+        let next = (0..levels.len() as isize)
+            .map(|level| {
+                (0..SIZE * SIZE)
+                    .filter(|tile| *tile != CENTRE)
+                    .filter(|tile| {
+                        let count = adjacency[*tile].iter()
+                            .filter(|(delta, n)| get(level + delta, *n))
+                            .count();
+                        lives(get(level, *tile), count)
+                    })
+                    .fold(0, |next, tile| next | (1 << tile))
+            })
+            .collect();
+        levels = next;
+
+        // Empty levels at the ends are added again next minute
+        while levels.len() > 1 && levels[0] == 0 {
+            levels.remove(0);
+        }
+        while levels.len() > 1 && levels[levels.len() - 1] == 0 {
+            levels.pop();
+        }
+    }
+
+    let bugs: u32 = levels.iter().map(|layout| layout.count_ones()).sum();
+    println!("There are {} bugs after {} minutes", bugs, minutes);
+
+    Ok(())
+}
+
+fn get_layout(filepath: String) -> anyhow::Result<Layout> {
+    let rows: Vec<String> = fs::read_to_string(filepath)?.lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    if rows.len() != SIZE || rows.iter().any(|row| row.len() != SIZE) {
+        return Err(anyhow!("Layout has to be {}x{} tiles", SIZE, SIZE));
+    }
+
+    let mut layout = 0;
+    for (y, row) in rows.iter().enumerate() {
+        for (x, c) in row.chars().enumerate() {
+            match c {
+                '#' => layout |= 1 << (y * SIZE + x),
+                '.' => {},
+                _ => return Err(anyhow!("Unknown tile {:?} at ({}, {})", c, x, y)),
+            }
+        }
+    }
+
+    Ok(layout)
+}
+
+// A bug survives with exactly one adjacent bug, an empty tile gets infested by one or two
+fn lives(bug: bool, adjacent: usize) -> bool {
+    adjacent == 1 || (!bug && adjacent == 2)
+}
+
+fn neighbours(tile: usize) -> Vec<usize> {
+    let (x, y) = (tile % SIZE, tile / SIZE);
+    let mut neighbours = Vec::new();
+
+    if y > 0 { neighbours.push(tile - SIZE); }
+    if y + 1 < SIZE { neighbours.push(tile + SIZE); }
+    if x > 0 { neighbours.push(tile - 1); }
+    if x + 1 < SIZE { neighbours.push(tile + 1); }
+
+    neighbours
+}
+
+// Neighbours with the level they are on relative to the tile: -1 is the level outside and 1 the
+// level inside the centre tile
+fn recursive_neighbours(tile: usize) -> Vec<(isize, usize)> {
+    let (x, y) = (tile % SIZE, tile / SIZE);
+    let middle = SIZE / 2;
+    let mut result = Vec::new();
+
+    for n in neighbours(tile) {
+        if n != CENTRE {
+            result.push((0, n));
+            continue;
+        }
+
+        // Side of the inner level facing the tile
+        let side: Vec<usize> = match (x as isize - middle as isize, y as isize - middle as isize) {
+            (0, -1) => (0..SIZE).collect(),
+            (0, 1) => (0..SIZE).map(|x| (SIZE - 1) * SIZE + x).collect(),
+            (-1, 0) => (0..SIZE).map(|y| y * SIZE).collect(),
+            _ => (0..SIZE).map(|y| y * SIZE + SIZE - 1).collect(),
+        };
+        result.extend(side.into_iter().map(|n| (1, n)));
+    }
+
+    // Tiles on the edge touch the tiles around the centre of the outer level
+    if y == 0 { result.push((-1, CENTRE - SIZE)); }
+    if y == SIZE - 1 { result.push((-1, CENTRE + SIZE)); }
+    if x == 0 { result.push((-1, CENTRE - 1)); }
+    if x == SIZE - 1 { result.push((-1, CENTRE + 1)); }
+
+    result
+}
+
+fn render(layout: Layout) -> String {
+    let mut text = String::new();
+
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            text.push(if layout & (1 << (y * SIZE + x)) != 0 { '#' } else { '.' });
+        }
+        text.push('\n');
+    }
+
+    text
+}
@@ -0,0 +1,263 @@
+use std::collections::HashSet;
+
+use rustyline::error::ReadlineError;
+use rustyline::Editor;
+
+use crate::day_2;
+use crate::intcode::{Event, Machine};
+use crate::intcode::watchdog::Limits;
+
+const CHECKPOINT: &str = "Security Checkpoint";
+// Taking an item or moving takes far less steps, more means the item traps the droid in a loop
+const SAFETY_STEP_LIMIT: u64 = 1_000_000;
+
+struct Room {
+    name: String,
+    doors: Vec<String>,
+    items: Vec<String>,
+}
+
+pub fn play(filepath: String) -> anyhow::Result<()> {
+    let mut machine = Machine::new(day_2::get_instructions(filepath)?);
+    let mut editor = Editor::<()>::new();
+
+    println!("Shortcuts: n, s, e, w to move, t <item> to take, d <item> to drop, i for the inventory");
+
+    let (mut text, mut halted) = communicate(&mut machine, None)?;
+    while !halted {
+        print!("{}", text);
+
+        let line = match editor.readline("> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => return Ok(()),
+            Err(error) => return Err(error.into()),
+        };
+
+        let command = expand_shortcut(line.trim());
+        if command.is_empty() {
+            continue;
+        }
+        editor.add_history_entry(line.trim());
+
+        let response = communicate(&mut machine, Some(&command))?;
+        text = response.0;
+        halted = response.1;
+    }
+
+    print!("{}", text);
+
+    Ok(())
+}
+
+// Explores the ship picking up every item that is safe to carry, then tries combinations of
+// items on the pressure-sensitive floor until it lets the droid through
+pub fn solve(filepath: String) -> anyhow::Result<()> {
+    let mut solver = Solver {
+        machine: Machine::new(day_2::get_instructions(filepath)?),
+        visited: HashSet::new(),
+        inventory: Vec::new(),
+        path: Vec::new(),
+        checkpoint: None,
+    };
+
+    let (text, _) = communicate(&mut solver.machine, None)?;
+    let start = parse_room(&text).ok_or(anyhow!("Game did not start in a room:\n{}", text))?;
+
+    solver.explore(&start, None)?;
+
+    let (path, floor) = solver.checkpoint.clone().ok_or(anyhow!("{} was not found", CHECKPOINT))?;
+    for door in path.iter() {
+        solver.command(door)?;
+    }
+
+    println!("Collected items: {}", solver.inventory.join(", "));
+
+    let text = solver.try_combinations(&floor)?;
+    let password = text.split(|c: char| !c.is_ascii_digit())
+        .rfind(|number| number.len() > 4)
+        .ok_or(anyhow!("Password was not found in:\n{}", text))?;
+
+    println!("Password for the main airlock is {}", password);
+
+    Ok(())
+}
+
+struct Solver {
+    machine: Machine,
+    visited: HashSet<String>,
+    inventory: Vec<String>,
+    path: Vec<String>, // doors taken from the start to the current room
+    checkpoint: Option<(Vec<String>, String)>, // path to the checkpoint and the door to the floor
+}
+
+impl Solver {
+    fn command(&mut self, command: &str) -> anyhow::Result<String> {
+        let (text, halted) = communicate(&mut self.machine, Some(command))?;
+
+        if halted {
+            return Err(anyhow!("Game ended after \"{}\":\n{}", command, text));
+        }
+
+        Ok(text)
+    }
+
+    // Depth-first search through the doors, returning to the room after every branch. Doors of
+    // the checkpoint are not followed, the floor behind it throws the droid back.
+    fn explore(&mut self, room: &Room, back: Option<&str>) -> anyhow::Result<()> {
+        self.visited.insert(room.name.to_owned());
+
+        for item in room.items.iter() {
+            if self.is_safe(item, room)? {
+                self.command(&format!("take {}", item))?;
+                self.inventory.push(item.to_owned());
+            } else {
+                info!("Leaving {} in {}", item, room.name);
+            }
+        }
+
+        if room.name == CHECKPOINT {
+            let floor = room.doors.iter().find(|door| Some(door.as_str()) != back)
+                .ok_or(anyhow!("{} has no door to the floor", CHECKPOINT))?;
+            self.checkpoint = Some((self.path.clone(), floor.to_owned()));
+
+            return Ok(());
+        }
+
+        for door in room.doors.iter().filter(|door| Some(door.as_str()) != back) {
+            let text = self.command(door)?;
+            let next = parse_room(&text).ok_or(anyhow!("Door {} did not lead to a room:\n{}", door, text))?;
+            let opposite = opposite(door)?;
+
+            if !self.visited.contains(&next.name) {
+                self.path.push(door.to_owned());
+                self.explore(&next, Some(opposite))?;
+                self.path.pop();
+            }
+
+            self.command(opposite)?;
+        }
+
+        Ok(())
+    }
+
+    // Takes the item on a copy of the game and checks that the droid can still move afterwards
+    fn is_safe(&self, item: &str, room: &Room) -> anyhow::Result<bool> {
+        let mut trial = self.machine.clone();
+        trial.set_limits(Limits { max_steps: Some(SAFETY_STEP_LIMIT), timeout: None });
+
+        let door = match room.doors.first() {
+            Some(door) => door,
+            None => return Ok(true),
+        };
+
+        for command in [format!("take {}", item), door.to_owned()].iter() {
+            match communicate(&mut trial, Some(command)) {
+                Ok((text, halted)) => {
+                    if halted || text.contains("can't move") {
+                        return Ok(false);
+                    }
+                },
+                Err(_) => return Ok(false), // most likely the step limit
+            }
+        }
+
+        Ok(true)
+    }
+
+    // Walks through the subsets of the inventory changing one item at a time (Gray code order).
+    // Returns the text of the successful attempt.
+    fn try_combinations(&mut self, floor: &str) -> anyhow::Result<String> {
+        let mut carried = vec![true; self.inventory.len()];
+
+        for i in 0..1usize << self.inventory.len() {
+            if i > 0 {
+                // Gray codes of i - 1 and i differ in the lowest set bit of i
+                let item = i.trailing_zeros() as usize;
+                let action = if carried[item] { "drop" } else { "take" };
+
+                self.command(&format!("{} {}", action, self.inventory[item]))?;
+                carried[item] = !carried[item];
+            }
+
+            let (text, halted) = communicate(&mut self.machine, Some(floor))?;
+            if halted {
+                let items: Vec<&str> = self.inventory.iter().zip(carried.iter())
+                    .filter(|(_, carried)| **carried)
+                    .map(|(item, _)| item.as_str())
+                    .collect();
+                println!("Floor accepted: {}", items.join(", "));
+
+                return Ok(text);
+            }
+        }
+
+        Err(anyhow!("No combination of items is accepted by the floor"))
+    }
+}
+
+// Sends the command and collects the text until the game asks for the next one. Returns the text
+// and whether the game ended.
+fn communicate(machine: &mut Machine, command: Option<&str>) -> anyhow::Result<(String, bool)> {
+    if let Some(command) = command {
+        machine.push_ascii(&format!("{}\n", command));
+    }
+
+    let mut text = String::new();
+    loop {
+        match machine.run()? {
+            Event::Output(value) => text.push(value as u8 as char),
+            Event::Input => return Ok((text, false)),
+            Event::Halt => return Ok((text, true)),
+            Event::Watchpoint(_) => {},
+        }
+    }
+}
+
+fn expand_shortcut(line: &str) -> String {
+    match line {
+        "n" => "north".to_string(),
+        "s" => "south".to_string(),
+        "e" => "east".to_string(),
+        "w" => "west".to_string(),
+        "i" => "inv".to_string(),
+        _ if line.starts_with("t ") => format!("take {}", &line[2..]),
+        _ if line.starts_with("d ") => format!("drop {}", &line[2..]),
+        _ => line.to_string(),
+    }
+}
+
+fn opposite(door: &str) -> anyhow::Result<&'static str> {
+    match door {
+        "north" => Ok("south"),
+        "south" => Ok("north"),
+        "east" => Ok("west"),
+        "west" => Ok("east"),
+        _ => Err(anyhow!("Unknown door {}", door)),
+    }
+}
+
+// Parses the last room description in the text. Being thrown back by the floor describes two
+// rooms, the droid ends up in the second one.
+fn parse_room(text: &str) -> Option<Room> {
+    let start = text.rfind("== ")?;
+    let mut lines = text[start..].lines();
+
+    let name = lines.next()?.trim_matches(|c| c == '=' || c == ' ').to_string();
+    let mut room = Room { name, doors: Vec::new(), items: Vec::new() };
+
+    let mut section = "";
+    for line in lines {
+        if line.ends_with(':') {
+            section = line;
+        } else if let Some(entry) = line.strip_prefix("- ") {
+            match section {
+                "Doors here lead:" => room.doors.push(entry.to_string()),
+                "Items here:" => room.items.push(entry.to_string()),
+                _ => {},
+            }
+        }
+    }
+
+    Some(room)
+}
+
@@ -1,123 +1,71 @@
-use anyhow;
-
 use std::io;
 use std::io::prelude::*;
+use std::fs;
 
 use crate::day_2;
+use crate::intcode::{Event, Machine};
+use crate::intcode::watchdog::Limits;
+
+#[derive(Debug, Default)]
+pub struct Options {
+    pub limits: Limits,
+    pub coverage: Option<String>, // path of the annotated disassembly
+    pub coverage_summary: Option<String>, // path of the JSON summary
+}
 
-pub fn diagnostic_tests(filename: String) -> anyhow::Result<()> {
+pub fn diagnostic_tests(filename: String, options: Options) -> anyhow::Result<()> {
     let mut instructions = day_2::get_instructions(filename)?;
 
-    run_program(&mut instructions)?;
+    run_program(&mut instructions, &options)?;
 
     Ok(())
 }
 
-pub fn run_program (instructions: &mut Vec<isize>) -> anyhow::Result<()> {
-    let mut ip = 0; // instruction pointer
+// Runs the program with input read from stdin and output printed to stdout
+pub fn run_program (instructions: &mut Vec<isize>, options: &Options) -> anyhow::Result<()> {
+    let mut machine = Machine::new(instructions.to_vec());
+    if options.limits != Limits::default() {
+        machine.set_limits(options.limits);
+    }
+    if options.coverage.is_some() || options.coverage_summary.is_some() {
+        machine.record_coverage();
+    }
 
-    loop {
-        let instruction = get_value(instructions, ip)?;
-        let opcode = instruction % 100;
-        let parameter_modes = instruction / 100;
+    let result = execute(&mut machine);
 
-        match opcode {
-            1 => {
-                let a = get_paramater(instructions, parameter_modes, 1, ip+1)?;
-                let b = get_paramater(instructions, parameter_modes, 10, ip+2)?;
-                let dst = get_value(instructions, ip+3)? as usize;
+    // Coverage of a failed run is still useful to see how far it went
+    if let Some(coverage) = machine.coverage() {
+        if let Some(path) = &options.coverage {
+            fs::write(path, coverage.listing(&machine))?;
+        }
+        if let Some(path) = &options.coverage_summary {
+            fs::write(path, coverage.summary(machine.memory().len()))?;
+        }
+    }
 
-                instructions[dst] = a+b;
-                ip += 4;
-            },
-            2 => {
-                let a = get_paramater(instructions, parameter_modes, 1, ip+1)?;
-                let b = get_paramater(instructions, parameter_modes, 10, ip+2)?;
-                let dst = get_value(instructions, ip+3)? as usize;
+    result?;
+    *instructions = machine.into_memory();
 
-                instructions[dst] = a*b;
-                ip += 4;
-            },
-            3 => {
+    Ok(())
+}
+
+fn execute(machine: &mut Machine) -> anyhow::Result<()> {
+    loop {
+        match machine.run()? {
+            Event::Input => {
                 let mut buf = String::new();
 
                 print!("<- ");
                 io::stdout().flush()?;
                 io::stdin().read_line(&mut buf)?;
 
-                let value = buf.trim().parse::<isize>()?;
-                let dst = get_value(instructions, ip+1)? as usize;
-                instructions[dst] = value;
-
-                ip += 2;
+                machine.push_input(buf.trim().parse::<isize>()?);
             },
-            4 => {
-                let pos = get_value(instructions, ip+1)? as usize;
-
-                println!("-> {}", instructions[pos]);
-
-                ip += 2;
-            },
-            5 => {
-                let a = get_paramater(instructions, parameter_modes, 1, ip+1)?;
-                if a != 0 {
-                    let jump = get_paramater(instructions, parameter_modes, 10, ip+2)? as usize;
-                    ip = jump;
-                } else {
-                    ip += 3;
-                }
-            },
-            6 => {
-                let a = get_paramater(instructions, parameter_modes, 1, ip+1)?;
-                if a == 0 {
-                    let jump = get_paramater(instructions, parameter_modes, 10, ip+2)? as usize;
-                    ip = jump;
-                } else {
-                    ip += 3;
-                }
-            },
-            7 => {
-                let a = get_paramater(instructions, parameter_modes, 1, ip+1)?;
-                let b = get_paramater(instructions, parameter_modes, 10, ip+2)?;
-                let dst = get_value(instructions, ip+3)? as usize;
-
-                instructions[dst] = match a < b {
-                    true => 1,
-                    false => 0,
-                };
-
-                ip += 4;
-            },
-            8 => {
-                let a = get_paramater(instructions, parameter_modes, 1, ip+1)?;
-                let b = get_paramater(instructions, parameter_modes, 10, ip+2)?;
-                let dst = get_value(instructions, ip+3)? as usize;
-
-                instructions[dst] = match a == b {
-                    true => 1,
-                    false => 0,
-                };
-
-                ip += 4;
-            },
-            99 => { break; },
-            op => { return Err(anyhow!("Operand {} is unknown", op)); }
-        };
+            Event::Output(value) => { println!("-> {}", value); },
+            Event::Halt => { break; },
+            Event::Watchpoint(hit) => { println!("Watchpoint: {}", hit); },
+        }
     }
 
     Ok(())
 }
-
-// divisor is a digit that needs to be read, hunders digit would be 100, thousands digit would be
-// 1000
-fn get_paramater(instructions: &Vec<isize>, parameter_modes: isize, divisor: isize, pos: usize) -> anyhow::Result<isize> {
-    match (parameter_modes / divisor) % 10 {
-        0 => get_value(instructions, get_value(instructions, pos)? as usize),
-        1 => get_value(instructions, pos),
-        parameter => return Err(anyhow!("Unknown paramter mode {}", parameter)),
-    }
-}
-
-fn get_value(instructions: &Vec<isize>, pos: usize) -> anyhow::Result<isize> {
-    instructions.get(pos).ok_or(anyhow!("No element at the position {}", pos)).map(|i| *i)
-}
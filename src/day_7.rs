@@ -0,0 +1,103 @@
+use itertools::Itertools;
+
+use crate::day_2;
+use crate::intcode::Machine;
+use crate::intcode::threaded::{Inbox, Outcome, Supervisor, Wire};
+
+const AMPLIFIERS: usize = 5;
+
+pub fn max_thruster_signal(filepath: String) -> anyhow::Result<()> {
+    let program = day_2::get_instructions(filepath)?;
+
+    let (phases, signal) = best_phases(&program, 0..5, false)?;
+    println!("Highest signal is {} with phases {:?}", signal, phases);
+
+    Ok(())
+}
+
+pub fn max_feedback_signal(filepath: String) -> anyhow::Result<()> {
+    let program = day_2::get_instructions(filepath)?;
+
+    let (phases, signal) = best_phases(&program, 5..10, true)?;
+    println!("Highest signal with feedback is {} with phases {:?}", signal, phases);
+
+    Ok(())
+}
+
+fn best_phases(program: &[isize], phases: std::ops::Range<isize>, feedback: bool) -> anyhow::Result<(Vec<isize>, isize)> {
+    let mut best: Option<(Vec<isize>, isize)> = None;
+
+    for phases in phases.permutations(AMPLIFIERS) {
+        let signal = amplify(program, &phases, feedback)?;
+
+        if best.as_ref().is_none_or(|(_, best)| signal > *best) {
+            best = Some((phases, signal));
+        }
+    }
+
+    best.ok_or(anyhow!("No phase settings to try"))
+}
+
+// Runs one amplifier per thread, every amplifier sending its output to the next one and the last
+// one back to the first with feedback. Returns the last output of the last amplifier.
+pub(crate) fn amplify(program: &[isize], phases: &[isize], feedback: bool) -> anyhow::Result<isize> {
+    let mut supervisor = Supervisor::new();
+    let channels: Vec<(Wire, Inbox)> = phases.iter().map(|_| supervisor.channel()).collect();
+    let wires: Vec<Wire> = channels.iter().map(|(wire, _)| wire.clone()).collect();
+
+    for (phase, wire) in phases.iter().zip(wires.iter()) {
+        wire.send(*phase);
+    }
+    wires[0].send(0);
+
+    // Without feedback the signal leaves through a wire nobody reads, the tap still sees it
+    let mut last = if feedback { wires[0].clone() } else { supervisor.channel().0 };
+    let signals = last.tap();
+
+    for (i, (_, inbox)) in channels.into_iter().enumerate() {
+        let output = wires.get(i + 1).unwrap_or(&last).clone();
+        supervisor.spawn(Machine::new(program.to_vec()), inbox, output);
+    }
+    drop(wires);
+    drop(last);
+
+    let outcome = supervisor.wait();
+    // Returns the error of a failed amplifier
+    supervisor.join()?;
+
+    match outcome {
+        Outcome::Halted => {},
+        Outcome::Blocked => return Err(anyhow!("Amplifiers with phases {:?} wait for input nobody sends", phases)),
+        Outcome::Failed => return Err(anyhow!("Amplifiers with phases {:?} failed", phases)),
+    }
+
+    signals.try_iter().last().ok_or(anyhow!("Amplifiers with phases {:?} sent no signal", phases))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHAIN: [isize; 17] = [3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0];
+    const FEEDBACK: [isize; 29] = [3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1, 28, 1005, 28, 6, 99, 0, 0, 5];
+
+    #[test]
+    fn chain() {
+        assert_eq!(amplify(&CHAIN, &[4, 3, 2, 1, 0], false).unwrap(), 43210);
+        assert_eq!(best_phases(&CHAIN, 0..5, false).unwrap(), (vec![4, 3, 2, 1, 0], 43210));
+    }
+
+    #[test]
+    fn feedback_loop_keeps_the_last_output() {
+        assert_eq!(amplify(&FEEDBACK, &[9, 8, 7, 6, 5], true).unwrap(), 139629729);
+    }
+
+    // Machines halting while their neighbours still send to them used to leave a value counted
+    // as in flight forever, which hung the supervisor now and then
+    #[test]
+    fn repeated_feedback_loops_finish() {
+        for _ in 0..500 {
+            assert_eq!(amplify(&FEEDBACK, &[9, 8, 7, 6, 5], true).unwrap(), 139629729);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::fs;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+const BLACK: u8 = 0;
+const WHITE: u8 = 1;
+const TRANSPARENT: u8 = 2;
+
+pub fn image_checksum(filepath: String, width: usize, height: usize) -> anyhow::Result<()> {
+    let layers = get_layers(filepath, width, height)?;
+
+    let layer = layers.iter()
+        .min_by_key(|layer| count(layer, 0))
+        .ok_or(anyhow!("Image has no layers"))?;
+
+    println!("Checksum of the layer with the fewest zeros is {}", count(layer, 1) * count(layer, 2));
+
+    Ok(())
+}
+
+pub fn decode_image(filepath: String, width: usize, height: usize, export: Option<String>) -> anyhow::Result<()> {
+    let image = composite(&get_layers(filepath, width, height)?, width * height);
+
+    for row in image.chunks(width) {
+        println!("{}", row.iter().map(|pixel| match *pixel {
+            BLACK => ' ',
+            WHITE => '█',
+            _ => '░',
+        }).collect::<String>());
+    }
+
+    if let Some(path) = export {
+        save(&path, &image, width, height)?;
+        println!("Image saved to {}", path);
+    }
+
+    Ok(())
+}
+
+fn get_layers(filepath: String, width: usize, height: usize) -> anyhow::Result<Vec<Vec<u8>>> {
+    let size = width * height;
+    if size == 0 {
+        return Err(anyhow!("Image of {}x{} pixels is empty", width, height));
+    }
+
+    let mut pixels = Vec::new();
+    for (i, c) in fs::read_to_string(filepath)?.trim().chars().enumerate() {
+        let digit = c.to_digit(10).ok_or(anyhow!("Pixel {} is not a digit: {:?}", i, c))?;
+        pixels.push(digit as u8);
+    }
+
+    if pixels.is_empty() || !pixels.len().is_multiple_of(size) {
+        return Err(anyhow!("{} pixels do not split into {}x{} layers", pixels.len(), width, height));
+    }
+
+    Ok(pixels.chunks(size).map(|layer| layer.to_vec()).collect())
+}
+
+fn count(layer: &[u8], digit: u8) -> usize {
+    layer.iter().filter(|pixel| **pixel == digit).count()
+}
+
+// Every pixel takes the colour of the first layer that is not transparent there. Layers are
+// ordered from the front.
+fn composite(layers: &[Vec<u8>], size: usize) -> Vec<u8> {
+    (0..size)
+        .map(|i| layers.iter().map(|layer| layer[i]).find(|pixel| *pixel != TRANSPARENT).unwrap_or(TRANSPARENT))
+        .collect()
+}
+
+// Format follows the extension, either .pgm or .png. Pixels transparent in every layer are grey.
+fn save(path: &str, image: &[u8], width: usize, height: usize) -> anyhow::Result<()> {
+    let grey: Vec<u8> = image.iter().map(|pixel| match *pixel {
+        BLACK => 0,
+        WHITE => 255,
+        _ => 128,
+    }).collect();
+
+    match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+        Some("pgm") => {
+            let mut data = format!("P5\n{} {}\n255\n", width, height).into_bytes();
+            data.extend(grey);
+            fs::write(path, data)?;
+        },
+        Some("png") => {
+            let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), width as u32, height as u32);
+            encoder.set_color(png::ColorType::Grayscale);
+            encoder.set_depth(png::BitDepth::Eight);
+            encoder.write_header()?.write_image_data(&grey)?;
+        },
+        _ => return Err(anyhow!("Unknown image format of {}, use .pgm or .png", path)),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use itertools::Itertools;
+
+use crate::day_2;
+use crate::intcode::Machine;
+
+const TEST_MODE: isize = 1;
+const SENSOR_BOOST_MODE: isize = 2;
+
+pub fn boost_keycode(filepath: String) -> anyhow::Result<()> {
+    let outputs = Machine::new(day_2::get_instructions(filepath)?).run_with_input(&[TEST_MODE])?;
+
+    // Self-test outputs every opcode that misbehaved, a working machine gets only the keycode
+    match outputs.as_slice() {
+        [keycode] => println!("BOOST keycode is {}", keycode),
+        [] => return Err(anyhow!("BOOST program did not output anything")),
+        malfunctioning => {
+            return Err(anyhow!("BOOST self-test reports malfunctioning opcodes: {}", malfunctioning.iter().join(", ")));
+        },
+    }
+
+    Ok(())
+}
+
+pub fn distress_coordinates(filepath: String) -> anyhow::Result<()> {
+    let outputs = Machine::new(day_2::get_instructions(filepath)?).run_with_input(&[SENSOR_BOOST_MODE])?;
+
+    let coordinates = outputs.last().ok_or(anyhow!("BOOST program did not output the coordinates"))?;
+    println!("Coordinates of the distress signal are {}", coordinates);
+
+    Ok(())
+}
@@ -0,0 +1,199 @@
+// Coverage of an Intcode run: which addresses were executed as code, which were only used as
+// data and which were never touched. Rendered either as an annotated disassembly or as a JSON
+// summary for other tools.
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use super::opcodes::{InstructionSet, Param};
+use super::{Machine, DENSE_MEMORY_LIMIT};
+
+const INSTRUCTION: u8 = 1; // start of an executed instruction
+const OPERAND: u8 = 1 << 1; // parameter of an executed instruction
+const READ: u8 = 1 << 2;
+const WRITTEN: u8 = 1 << 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Category {
+    Code,
+    Data,
+    Untouched,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Coverage {
+    cells: Vec<u8>,
+    sparse: BTreeMap<usize, u8>, // touched addresses at and past `DENSE_MEMORY_LIMIT`, in order
+}
+
+impl Coverage {
+    pub fn new() -> Coverage {
+        Coverage::default()
+    }
+
+    pub(super) fn executed(&mut self, ip: usize, arity: usize) {
+        self.mark(ip, INSTRUCTION);
+        for address in ip+1..=ip+arity {
+            self.mark(address, OPERAND);
+        }
+    }
+
+    pub(super) fn read(&mut self, address: usize) {
+        self.mark(address, READ);
+    }
+
+    pub(super) fn written(&mut self, address: usize) {
+        self.mark(address, WRITTEN);
+    }
+
+    fn mark(&mut self, address: usize, flag: u8) {
+        if address >= DENSE_MEMORY_LIMIT {
+            *self.sparse.entry(address).or_insert(0) |= flag;
+            return;
+        }
+
+        if address >= self.cells.len() {
+            self.cells.resize(address + 1, 0);
+        }
+
+        self.cells[address] |= flag;
+    }
+
+    fn flags(&self, address: usize) -> u8 {
+        match self.cells.get(address) {
+            Some(flags) => *flags,
+            None => self.sparse.get(&address).copied().unwrap_or(0),
+        }
+    }
+
+    fn category(&self, address: usize) -> Category {
+        let flags = self.flags(address);
+
+        if flags & (INSTRUCTION | OPERAND) != 0 {
+            Category::Code
+        } else if flags & (READ | WRITTEN) != 0 {
+            Category::Data
+        } else {
+            Category::Untouched
+        }
+    }
+
+    // Disassembly of the memory where every line starts with three columns: x for executed code,
+    // r for read and w for written data. Past the loaded memory only touched addresses are listed.
+    pub fn listing(&self, machine: &Machine) -> String {
+        let (memory, instructions) = (machine.memory(), machine.instructions());
+        let size = memory.len().max(self.cells.len());
+        let mut listing = String::new();
+        let mut address = 0;
+
+        while address < size {
+            let flags = self.flags(address);
+            let columns = columns(flags);
+
+            let disassembled = if flags & INSTRUCTION != 0 { disassemble(memory, address, instructions) } else { None };
+
+            match disassembled {
+                Some((text, length)) => {
+                    writeln!(listing, "{} {:>6}: {}", columns, address, text).unwrap();
+                    address += length;
+                },
+                None => {
+                    let value = memory.get(address).copied().unwrap_or(0);
+                    writeln!(listing, "{} {:>6}: {}", columns, address, value).unwrap();
+                    address += 1;
+                },
+            }
+        }
+
+        for (address, flags) in self.sparse.range(address..) {
+            writeln!(listing, "{} {:>6}: {}", columns(*flags), address, machine.peek(*address)).unwrap();
+        }
+
+        listing
+    }
+
+    // JSON object with the number of addresses in each category and their ranges. Addresses past
+    // the memory size only count when they were touched.
+    pub fn summary(&self, memory_size: usize) -> String {
+        let size = memory_size.max(self.cells.len());
+        let mut categories = Vec::new();
+
+        for (name, category) in [("code", Category::Code), ("data", Category::Data), ("untouched", Category::Untouched)].iter() {
+            let ranges = self.ranges(size, *category);
+            let count: usize = ranges.iter().map(|(start, end)| end - start + 1).sum();
+            let ranges: Vec<String> = ranges.iter().map(|(start, end)| format!("[{},{}]", start, end)).collect();
+
+            categories.push(format!("\"{}\":{{\"count\":{},\"ranges\":[{}]}}", name, count, ranges.join(",")));
+        }
+
+        let instructions = self.cells.iter().chain(self.sparse.values()).filter(|flags| *flags & INSTRUCTION != 0).count();
+
+        format!("{{\"size\":{},\"instructions\":{},{}}}", size, instructions, categories.join(","))
+    }
+
+    // Inclusive ranges of consecutive addresses of the category
+    fn ranges(&self, size: usize, category: Category) -> Vec<(usize, usize)> {
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+
+        let touched = self.sparse.range(size..).map(|(address, _)| *address);
+
+        for address in (0..size).chain(touched).filter(|address| self.category(*address) == category) {
+            match ranges.last_mut() {
+                Some((_, end)) if *end + 1 == address => { *end = address; },
+                _ => { ranges.push((address, address)); },
+            }
+        }
+
+        ranges
+    }
+}
+
+fn columns(flags: u8) -> String {
+    format!("{}{}{}",
+        if flags & (INSTRUCTION | OPERAND) != 0 { 'x' } else { '-' },
+        if flags & READ != 0 { 'r' } else { '-' },
+        if flags & WRITTEN != 0 { 'w' } else { '-' })
+}
+
+// Text of the instruction at the address and the number of words it takes. Parameters in
+// position mode are written as [address], in relative mode as [rb+offset].
+pub fn disassemble(memory: &[isize], address: usize, instructions: &InstructionSet) -> Option<(String, usize)> {
+    let instruction = *memory.get(address)?;
+    let opcode = instructions.get(instruction % 100)?;
+
+    let mut params = Vec::with_capacity(opcode.arity());
+    for (i, param) in opcode.params().iter().enumerate() {
+        let raw = memory.get(address + i + 1).copied().unwrap_or(0);
+
+        params.push(match (super::parameter_mode(instruction, i + 1), param) {
+            (0, _) => format!("[{}]", raw),
+            (1, Param::Read) => format!("{}", raw),
+            (2, _) => format!("[rb{:+}]", raw),
+            _ => format!("?{}", raw),
+        });
+    }
+
+    let text = if params.is_empty() {
+        opcode.name().to_owned()
+    } else {
+        format!("{} {}", opcode.name(), params.join(", "))
+    };
+
+    Some((text, opcode.arity() + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn far_writes_only_report_touched_addresses() {
+        let mut machine = Machine::new(vec![1101, 1, 1, 1 << 40, 99]);
+        machine.record_coverage();
+        machine.run_with_input(&[]).unwrap();
+
+        let summary = machine.coverage().unwrap().summary(machine.memory().len());
+        assert_eq!(summary, format!(
+            "{{\"size\":5,\"instructions\":2,\"code\":{{\"count\":5,\"ranges\":[[0,4]]}},\"data\":{{\"count\":1,\"ranges\":[[{0},{0}]]}},\"untouched\":{{\"count\":0,\"ranges\":[]}}}}",
+            1usize << 40));
+    }
+}
@@ -0,0 +1,217 @@
+// Interactive debugger for Intcode programs. Reads commands from stdin, type "help" for the list.
+// Executed instructions are recorded, so the debugger can also go back in time.
+use std::io;
+use std::io::prelude::*;
+
+use crate::day_2;
+use super::{Event, Machine};
+use super::watch::{Action, Watch, Watchpoint};
+
+// Instructions remembered for going back
+const HISTORY_LIMIT: usize = 1_000_000;
+
+const HELP: &str = "\
+step [n]                      execute n instructions (default 1)
+continue                      run until halt, missing input or a watchpoint
+back [n]                      undo n instructions (default 1)
+rwrite <addr>                 go back to right before the previous write to the address
+input <value>...              queue input values
+watch <r|w|rw> <addr>[..<addr>] [log]
+                              watch reads and/or writes of addresses, pause or only log
+unwatch <id>                  remove a watchpoint
+watches                       list watchpoints
+print <addr> [count]          print memory
+set <addr> <value>            change memory
+regs                          print ip, relative base and history length
+quit";
+
+// Patches are written as "address=value" and applied before the program starts
+pub fn debug(filepath: String, patches: Vec<String>) -> anyhow::Result<()> {
+    let mut machine = Machine::new(day_2::get_instructions(filepath)?);
+
+    for patch in patches {
+        let (address, value) = patch.split_once('=').ok_or(anyhow!("Patch {} is not address=value", patch))?;
+        machine.poke(address.trim().parse::<usize>()?, value.trim().parse::<isize>()?);
+    }
+
+    machine.record_history(HISTORY_LIMIT);
+
+    println!("Intcode debugger, type \"help\" for the list of commands");
+
+    let stdin = io::stdin();
+    loop {
+        print!("(ip {}) ", machine.ip());
+        io::stdout().flush()?;
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            break;
+        }
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match execute(&mut machine, &words) {
+            Ok(true) => { break; },
+            Ok(false) => {},
+            Err(error) => { println!("{}", error); },
+        }
+    }
+
+    Ok(())
+}
+
+// Returns true if the debugger should quit
+fn execute(machine: &mut Machine, words: &[&str]) -> anyhow::Result<bool> {
+    let (command, args) = match words.split_first() {
+        Some((command, args)) => (*command, args),
+        None => return Ok(false),
+    };
+
+    match command {
+        "help" | "h" => { println!("{}", HELP); },
+        "step" | "s" => {
+            let count = args.first().map(|n| n.parse::<usize>()).transpose()?.unwrap_or(1);
+
+            for _ in 0..count {
+                let event = machine.step()?;
+                report_logged(machine);
+
+                if let Some(event) = event {
+                    if report(event) {
+                        break;
+                    }
+                }
+            }
+        },
+        "continue" | "c" => {
+            loop {
+                let event = machine.run()?;
+                report_logged(machine);
+
+                if report(event) {
+                    break;
+                }
+            }
+        },
+        "back" | "b" => {
+            let count = args.first().map(|n| n.parse::<usize>()).transpose()?.unwrap_or(1);
+
+            for _ in 0..count {
+                if !machine.step_back() {
+                    println!("Reached the beginning of the history");
+                    break;
+                }
+            }
+        },
+        "rwrite" => {
+            let address = args.first().ok_or(anyhow!("Expected an address"))?.parse::<usize>()?;
+
+            match machine.run_back_to_write(address) {
+                Some(hit) => { println!("Previous write: {}", hit); },
+                None => { println!("No recorded instruction wrote to {}", address); },
+            }
+        },
+        "input" | "i" => {
+            for value in args {
+                machine.push_input(value.parse::<isize>()?);
+            }
+        },
+        "watch" | "w" => {
+            let watch = match args.first() {
+                Some(&"r") => Watch::Reads,
+                Some(&"w") => Watch::Writes,
+                Some(&"rw") => Watch::All,
+                _ => return Err(anyhow!("Expected r, w or rw")),
+            };
+            let addresses = parse_range(args.get(1).ok_or(anyhow!("Expected an address"))?)?;
+            let action = match args.get(2) {
+                Some(&"log") => Action::Log,
+                None => Action::Pause,
+                Some(other) => return Err(anyhow!("Unknown watchpoint action {}", other)),
+            };
+
+            let id = machine.add_watchpoint(Watchpoint::new(addresses, watch, action));
+            println!("Watchpoint {} set", id);
+        },
+        "unwatch" => {
+            let id = args.first().ok_or(anyhow!("Expected a watchpoint id"))?.parse::<usize>()?;
+
+            if machine.remove_watchpoint(id).is_none() {
+                println!("There is no watchpoint {}", id);
+            }
+        },
+        "watches" => {
+            for (id, watchpoint) in machine.watchpoints() {
+                println!("{}: {}", id, watchpoint);
+            }
+        },
+        "print" | "p" => {
+            let address = args.first().ok_or(anyhow!("Expected an address"))?.parse::<usize>()?;
+            let count = args.get(1).map(|n| n.parse::<usize>()).transpose()?.unwrap_or(1);
+
+            let end = address.checked_add(count).ok_or(anyhow!("{} values from {} run past the memory", count, address))?;
+
+            let values: Vec<String> = (address..end).map(|a| machine.peek(a).to_string()).collect();
+            println!("{}: {}", address, values.join(","));
+        },
+        "set" => {
+            let address = args.first().ok_or(anyhow!("Expected an address"))?.parse::<usize>()?;
+            let value = args.get(1).ok_or(anyhow!("Expected a value"))?.parse::<isize>()?;
+
+            machine.poke(address, value);
+        },
+        "regs" | "r" => {
+            println!("ip {}, relative base {}, {} instructions recorded{}", machine.ip(), machine.relative_base(),
+                machine.history_len(), if machine.is_halted() { ", halted" } else { "" });
+        },
+        "quit" | "q" => { return Ok(true); },
+        _ => { println!("Unknown command {}, type \"help\" for the list of commands", command); },
+    }
+
+    Ok(false)
+}
+
+// Prints the event, returns true if execution should stop
+fn report(event: Event) -> bool {
+    match event {
+        Event::Output(value) => { println!("-> {}", value); false },
+        Event::Input => { println!("Program waits for input"); true },
+        Event::Halt => { println!("Program halted"); true },
+        Event::Watchpoint(hit) => { println!("Watchpoint: {}", hit); true },
+    }
+}
+
+fn report_logged(machine: &mut Machine) {
+    for hit in machine.take_logged() {
+        println!("Logged: {}", hit);
+    }
+}
+
+// Either a single address or an inclusive range written as "from..to"
+fn parse_range(text: &str) -> anyhow::Result<std::ops::RangeInclusive<usize>> {
+    match text.find("..") {
+        Some(i) => {
+            let (from, to) = (text[..i].parse::<usize>()?, text[i+2..].parse::<usize>()?);
+            if from > to {
+                return Err(anyhow!("Range {} is empty, it has to go from the lower address", text));
+            }
+
+            Ok(from..=to)
+        },
+        None => {
+            let address = text.parse::<usize>()?;
+            Ok(address..=address)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges() {
+        assert_eq!(parse_range("7").unwrap(), 7..=7);
+        assert_eq!(parse_range("5..10").unwrap(), 5..=10);
+        assert!(parse_range("10..5").is_err());
+    }
+}
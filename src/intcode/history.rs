@@ -0,0 +1,117 @@
+// Undo log of the Intcode machine. Every executed instruction leaves a record of what it changed,
+// which is enough to restore the state from before the instruction.
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone)]
+pub(super) struct Record {
+    pub ip: usize,
+    pub relative_base: isize,
+    pub writes: Vec<(usize, isize)>, // address and the value before the write, in write order
+    pub input: Option<isize>, // input value consumed by the instruction
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct History {
+    records: VecDeque<Record>,
+    limit: usize, // oldest records are forgotten past the limit
+}
+
+impl History {
+    pub fn new(limit: usize) -> History {
+        History { records: VecDeque::new(), limit }
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    // Starts the record of an instruction about to be executed
+    pub fn begin(&mut self, ip: usize, relative_base: isize) {
+        self.records.push_back(Record { ip, relative_base, writes: Vec::new(), input: None });
+    }
+
+    // Keeps the record of an executed instruction, forgetting the oldest ones past the limit
+    pub fn commit(&mut self) {
+        while self.records.len() > self.limit {
+            self.records.pop_front();
+        }
+    }
+
+    // Drops the record of an instruction which did not execute after all
+    pub fn discard(&mut self) {
+        self.records.pop_back();
+    }
+
+    pub fn record_write(&mut self, address: usize, old: isize) {
+        if let Some(record) = self.records.back_mut() {
+            record.writes.push((address, old));
+        }
+    }
+
+    pub fn record_input(&mut self, value: isize) {
+        if let Some(record) = self.records.back_mut() {
+            record.input = Some(value);
+        }
+    }
+
+    // Whether any remembered instruction wrote to the address
+    pub fn wrote(&self, address: usize) -> bool {
+        self.records.iter().any(|record| record.writes.iter().any(|(written, _)| *written == address))
+    }
+
+    pub fn last(&self) -> Option<&Record> {
+        self.records.back()
+    }
+
+    pub fn pop(&mut self) -> Option<Record> {
+        self.records.pop_back()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Event, Machine};
+
+    #[test]
+    fn waiting_for_input_keeps_the_oldest_record() {
+        let mut machine = Machine::new(vec![1101, 1, 1, 9, 3, 9, 99]);
+        machine.record_history(1);
+
+        machine.step().unwrap();
+        assert_eq!(machine.history_len(), 1);
+
+        assert_eq!(machine.run().unwrap(), Event::Input);
+        assert_eq!(machine.history_len(), 1);
+        assert!(machine.step_back());
+        assert_eq!(machine.ip(), 0);
+    }
+
+    #[test]
+    fn failed_instruction_leaves_no_record() {
+        let mut machine = Machine::new(vec![1101, 1, 1, 9, 1102, isize::MAX, 2, 9, 99, 0]);
+        machine.record_history(10);
+
+        machine.step().unwrap();
+        assert!(machine.step().is_err());
+        assert!(machine.step().is_err());
+        assert_eq!(machine.history_len(), 1);
+
+        assert!(machine.step_back());
+        assert_eq!((machine.ip(), machine.peek(9)), (0, 0));
+    }
+
+    #[test]
+    fn running_back_to_an_unwritten_address_keeps_the_position() {
+        let mut machine = Machine::new(vec![1101, 1, 1, 9, 1101, 2, 2, 10, 99, 0, 0]);
+        machine.record_history(10);
+        machine.step().unwrap();
+        machine.step().unwrap();
+
+        assert_eq!(machine.run_back_to_write(5), None);
+        assert_eq!((machine.ip(), machine.history_len()), (8, 2));
+
+        let hit = machine.run_back_to_write(9).unwrap();
+        assert_eq!((hit.ip, hit.old, hit.new), (0, 0, 2));
+        assert_eq!(machine.ip(), 0);
+    }
+}
@@ -0,0 +1,477 @@
+// Intcode virtual machine shared by every challenge that runs an Intcode program. The machine
+// never does I/O on its own: it stops with an `Event` whenever it needs input or produces an
+// output, so the caller decides where values come from and where they go.
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Instant;
+
+pub mod coverage;
+pub mod debugger;
+mod history;
+pub mod opcodes;
+pub mod threaded;
+pub mod watch;
+pub mod watchdog;
+
+use coverage::Coverage;
+use history::History;
+use opcodes::{Flow, InstructionSet, Param};
+use watch::{Access, Action, Hit, Watchpoint};
+use watchdog::{Limits, Watchdog};
+
+// How many instructions are executed between checks of the timeout
+const TIMEOUT_CHECK_INTERVAL: u64 = 1024;
+// Addresses from here on are kept in a map, so a program writing far away does not allocate
+// everything in between
+const DENSE_MEMORY_LIMIT: usize = 1 << 24;
+
+// Intcode programs compute with values that need 64 bits
+const _: () = assert!(std::mem::size_of::<isize>() >= 8);
+
+lazy_static! {
+    static ref STANDARD_INSTRUCTIONS: Arc<InstructionSet> = Arc::new(InstructionSet::standard());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    // Input instruction was reached while the input queue was empty. The instruction is not
+    // consumed, so running the machine again after `push_input` retries it.
+    Input,
+    Output(isize),
+    Halt,
+    // Instruction at `ip` touched memory watched by a pausing watchpoint
+    Watchpoint(Hit),
+}
+
+// Cloning a machine snapshots its whole state, which takes a copy of the memory and the input
+// queue while the instruction set is shared between the clones
+#[derive(Debug, Clone)]
+pub struct Machine {
+    memory: Vec<isize>,
+    sparse: HashMap<usize, isize>, // written memory at and past `DENSE_MEMORY_LIMIT`
+    ip: usize, // instruction pointer
+    relative_base: isize,
+    input: VecDeque<isize>,
+    halted: bool,
+    instructions: Arc<InstructionSet>,
+    watchpoints: Vec<Option<Watchpoint>>, // removed watchpoints leave a hole to keep ids stable
+    paused: VecDeque<Hit>, // hits of pausing watchpoints not yet reported
+    logged: VecDeque<Hit>, // hits of logging watchpoints not yet taken by `take_logged`
+    history: Option<History>,
+    watchdog: Option<Watchdog>,
+    coverage: Option<Coverage>,
+}
+
+impl Machine {
+    pub fn new(program: Vec<isize>) -> Machine {
+        Machine::with_instructions(program, STANDARD_INSTRUCTIONS.clone())
+    }
+
+    // Machine running its program on a custom instruction set, see `opcodes::InstructionSet`
+    pub fn with_instructions(program: Vec<isize>, instructions: Arc<InstructionSet>) -> Machine {
+        Machine {
+            memory: program,
+            sparse: HashMap::new(),
+            ip: 0,
+            relative_base: 0,
+            input: VecDeque::new(),
+            halted: false,
+            instructions,
+            watchpoints: Vec::new(),
+            paused: VecDeque::new(),
+            logged: VecDeque::new(),
+            history: None,
+            watchdog: None,
+            coverage: None,
+        }
+    }
+
+    pub fn push_input(&mut self, value: isize) {
+        self.input.push_back(value);
+    }
+
+    // Queues the text as ASCII codes for programs talking in text
+    pub fn push_ascii(&mut self, text: &str) {
+        self.input.extend(text.bytes().map(|c| c as isize));
+    }
+
+    pub fn take_input(&mut self) -> Option<isize> {
+        let value = self.input.pop_front();
+
+        if let (Some(history), Some(value)) = (&mut self.history, value) {
+            history.record_input(value);
+        }
+
+        value
+    }
+
+    pub fn instructions(&self) -> &InstructionSet {
+        &self.instructions
+    }
+
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    pub fn relative_base(&self) -> isize {
+        self.relative_base
+    }
+
+    pub fn adjust_relative_base(&mut self, delta: isize) -> anyhow::Result<()> {
+        self.relative_base = self.relative_base.checked_add(delta)
+            .ok_or_else(|| anyhow!("Relative base {} overflows when adjusted by {} at {}", self.relative_base, delta, self.ip))?;
+
+        Ok(())
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    // Memory up to the furthest address written below `DENSE_MEMORY_LIMIT`
+    pub fn memory(&self) -> &[isize] {
+        &self.memory
+    }
+
+    pub fn into_memory(self) -> Vec<isize> {
+        self.memory
+    }
+
+    // Reads memory without triggering watchpoints. Memory past the loaded program reads as zero.
+    pub fn peek(&self, address: usize) -> isize {
+        if address >= DENSE_MEMORY_LIMIT && address >= self.memory.len() {
+            return self.sparse.get(&address).copied().unwrap_or(0);
+        }
+
+        self.memory.get(address).copied().unwrap_or(0)
+    }
+
+    // Reads memory on behalf of the program at the current instruction
+    pub fn read(&mut self, address: usize) -> isize {
+        let value = self.peek(address);
+        self.check_watchpoints(address, Access::Read, value, value);
+
+        if let Some(coverage) = &mut self.coverage {
+            coverage.read(address);
+        }
+
+        value
+    }
+
+    // Writes memory without triggering watchpoints. Memory grows on demand when writing past the
+    // loaded program.
+    pub fn poke(&mut self, address: usize, value: isize) {
+        if address >= DENSE_MEMORY_LIMIT && address >= self.memory.len() {
+            self.sparse.insert(address, value);
+            return;
+        }
+
+        if address >= self.memory.len() {
+            self.memory.resize(address + 1, 0);
+        }
+
+        self.memory[address] = value;
+    }
+
+    // Writes memory on behalf of the program at the current instruction
+    pub fn write(&mut self, address: usize, value: isize) {
+        let old = self.peek(address);
+        self.poke(address, value);
+        self.check_watchpoints(address, Access::Write, old, value);
+
+        if let Some(history) = &mut self.history {
+            history.record_write(address, old);
+        }
+        if let Some(coverage) = &mut self.coverage {
+            coverage.written(address);
+        }
+    }
+
+    // Starts tracking which addresses are executed and which are used as data
+    pub fn record_coverage(&mut self) {
+        self.coverage = Some(Coverage::new());
+    }
+
+    pub fn coverage(&self) -> Option<&Coverage> {
+        self.coverage.as_ref()
+    }
+
+    // Starts recording every executed instruction so it can be undone. Only the last `limit`
+    // instructions are remembered.
+    pub fn record_history(&mut self, limit: usize) {
+        self.history = Some(History::new(limit));
+    }
+
+    // Number of instructions that can be undone
+    pub fn history_len(&self) -> usize {
+        self.history.as_ref().map_or(0, |history| history.len())
+    }
+
+    // Undoes the last executed instruction. Returns false if there is nothing to undo. Outputs
+    // already reported are not taken back.
+    pub fn step_back(&mut self) -> bool {
+        let record = match self.history.as_mut().and_then(|history| history.pop()) {
+            Some(record) => record,
+            None => return false,
+        };
+
+        for (address, old) in record.writes.into_iter().rev() {
+            self.poke(address, old);
+        }
+        if let Some(value) = record.input {
+            self.input.push_front(value);
+        }
+
+        self.ip = record.ip;
+        self.relative_base = record.relative_base;
+        self.halted = false;
+        self.paused.clear();
+
+        true
+    }
+
+    // Undoes instructions up to and including the last one that wrote to the address. The machine
+    // is left right before that instruction, which is returned as a hit. Returns None without
+    // undoing anything if no recorded instruction wrote to the address.
+    pub fn run_back_to_write(&mut self, address: usize) -> Option<Hit> {
+        if !self.history.as_ref()?.wrote(address) {
+            return None;
+        }
+
+        loop {
+            let record = self.history.as_ref()?.last()?;
+            let ip = record.ip;
+            // The first write of the instruction holds the value from before it
+            let old = record.writes.iter().find(|(written, _)| *written == address).map(|(_, old)| *old);
+            let new = self.peek(address);
+
+            self.step_back();
+
+            if let Some(old) = old {
+                return Some(Hit { ip, address, access: Access::Write, old, new });
+            }
+        }
+    }
+
+    // Returns the id used to remove the watchpoint
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) -> usize {
+        self.watchpoints.push(Some(watchpoint));
+
+        self.watchpoints.len() - 1
+    }
+
+    pub fn remove_watchpoint(&mut self, id: usize) -> Option<Watchpoint> {
+        self.watchpoints.get_mut(id).and_then(|watchpoint| watchpoint.take())
+    }
+
+    pub fn watchpoints(&self) -> impl Iterator<Item = (usize, &Watchpoint)> {
+        self.watchpoints.iter().enumerate()
+            .filter_map(|(id, watchpoint)| watchpoint.as_ref().map(|watchpoint| (id, watchpoint)))
+    }
+
+    // Hits of logging watchpoints since the last call, oldest first. They pile up until taken.
+    pub fn take_logged(&mut self) -> impl Iterator<Item = Hit> + '_ {
+        self.logged.drain(..)
+    }
+
+    fn check_watchpoints(&mut self, address: usize, access: Access, old: isize, new: isize) {
+        if self.watchpoints.is_empty() {
+            return;
+        }
+
+        let hit = Hit { ip: self.ip, address, access, old, new };
+        let mut pause = false;
+
+        for watchpoint in self.watchpoints.iter().flatten() {
+            if watchpoint.matches(address, access) {
+                match watchpoint.action {
+                    Action::Pause => { pause = true; },
+                    Action::Log => { self.logged.push_back(hit); },
+                }
+            }
+        }
+
+        if pause {
+            self.paused.push_back(hit);
+        }
+    }
+
+    // Stops the program with `watchdog::WatchdogError` once it runs past the limits
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.watchdog = Some(Watchdog::new(limits));
+    }
+
+    // Runs until the machine halts, produces an output or waits for input
+    pub fn run(&mut self) -> anyhow::Result<Event> {
+        let started = Instant::now();
+        let mut steps: u64 = 0;
+
+        let result = loop {
+            match self.step() {
+                Ok(Some(event)) => break Ok(event),
+                Err(error) => break Err(error),
+                Ok(None) => {},
+            }
+
+            steps += 1;
+            if steps.is_multiple_of(TIMEOUT_CHECK_INTERVAL) {
+                if let Some(watchdog) = &self.watchdog {
+                    if let Some(reason) = watchdog.check_time(started.elapsed()) {
+                        break Err(watchdog.error(reason, self.ip, &self.instructions).into());
+                    }
+                }
+            }
+        };
+
+        if let Some(watchdog) = &mut self.watchdog {
+            watchdog.add_busy(started.elapsed());
+        }
+
+        result
+    }
+
+    // Runs to completion feeding `inputs` and collects every output. Fails if the program asks
+    // for more input than was given.
+    pub fn run_with_input(&mut self, inputs: &[isize]) -> anyhow::Result<Vec<isize>> {
+        self.input.extend(inputs);
+
+        let mut outputs = Vec::new();
+        loop {
+            match self.run()? {
+                Event::Output(value) => outputs.push(value),
+                Event::Input => return Err(anyhow!("Program at {} ran out of input", self.ip)),
+                Event::Halt => return Ok(outputs),
+                Event::Watchpoint(hit) => { debug!("Watchpoint: {}", hit); },
+            }
+        }
+    }
+
+    // Executes a single instruction. Returns an event if the instruction produced one.
+    pub fn step(&mut self) -> anyhow::Result<Option<Event>> {
+        if let Some(hit) = self.paused.pop_front() {
+            return Ok(Some(Event::Watchpoint(hit)));
+        }
+
+        if self.halted {
+            return Ok(Some(Event::Halt));
+        }
+
+        if let Some(watchdog) = &self.watchdog {
+            if let Some(reason) = watchdog.check_steps() {
+                return Err(watchdog.error(reason, self.ip, &self.instructions).into());
+            }
+        }
+
+        let ip = self.ip;
+        let instruction = self.peek(ip);
+        // Keeps the instruction set alive while the handler borrows the machine
+        let instructions = self.instructions.clone();
+        let opcode = instructions.get(instruction % 100)
+            .ok_or_else(|| anyhow!("Operand {} at {} is unknown", instruction % 100, self.ip))?;
+
+        let mut arguments = Vec::with_capacity(opcode.arity());
+        for (i, param) in opcode.params().iter().enumerate() {
+            arguments.push(match param {
+                Param::Read => self.parameter(instruction, i + 1)?,
+                Param::Write => self.destination(instruction, i + 1)? as isize,
+            });
+        }
+
+        let next = self.ip + 1 + opcode.arity();
+
+        if let Some(history) = &mut self.history {
+            history.begin(self.ip, self.relative_base);
+        }
+
+        let flow = match (opcode.handler())(self, &arguments) {
+            Ok(flow) => flow,
+            Err(error) => {
+                // The failed instruction is not part of the history, retrying it adds nothing
+                if let Some(history) = &mut self.history {
+                    history.discard();
+                }
+
+                return Err(error);
+            },
+        };
+
+        if flow != Flow::Wait {
+            if let Some(watchdog) = &mut self.watchdog {
+                watchdog.record(ip, instruction);
+            }
+            if let Some(coverage) = &mut self.coverage {
+                coverage.executed(ip, opcode.arity());
+            }
+            if let Some(history) = &mut self.history {
+                history.commit();
+            }
+        }
+
+        match flow {
+            Flow::Next => { self.ip = next; },
+            Flow::Jump(target) => { self.ip = target; },
+            Flow::Output(value) => {
+                self.ip = next;
+                return Ok(Some(Event::Output(value)));
+            },
+            Flow::Wait => {
+                if let Some(history) = &mut self.history {
+                    history.discard();
+                }
+
+                return Ok(Some(Event::Input));
+            },
+            Flow::Halt => {
+                self.halted = true;
+                return Ok(Some(Event::Halt));
+            },
+        };
+
+        // Hits of an instruction producing another event are reported on the next step
+        Ok(self.paused.pop_front().map(Event::Watchpoint))
+    }
+
+    // Value of the n-th parameter (counted from 1) of the current instruction
+    fn parameter(&mut self, instruction: isize, n: usize) -> anyhow::Result<isize> {
+        let raw = self.peek(self.ip + n);
+
+        match parameter_mode(instruction, n) {
+            0 => Ok(self.read(to_address(raw)?)),
+            1 => Ok(raw),
+            2 => Ok(self.read(self.relative_address(raw)?)),
+            mode => Err(anyhow!("Unknown parameter mode {} at {}", mode, self.ip)),
+        }
+    }
+
+    // Address the n-th parameter (counted from 1) of the current instruction writes to
+    fn destination(&self, instruction: isize, n: usize) -> anyhow::Result<usize> {
+        let raw = self.peek(self.ip + n);
+
+        match parameter_mode(instruction, n) {
+            0 => to_address(raw),
+            2 => self.relative_address(raw),
+            mode => Err(anyhow!("Parameter mode {} can not be written to at {}", mode, self.ip)),
+        }
+    }
+
+    fn relative_address(&self, offset: isize) -> anyhow::Result<usize> {
+        let address = self.relative_base.checked_add(offset)
+            .ok_or_else(|| anyhow!("Relative address {}{:+} overflows at {}", self.relative_base, offset, self.ip))?;
+
+        to_address(address)
+    }
+}
+
+// Mode digit of the n-th parameter. Modes start at the hundreds digit.
+pub(super) fn parameter_mode(instruction: isize, n: usize) -> isize {
+    (instruction / 10isize.pow(n as u32 + 1)) % 10
+}
+
+fn to_address(value: isize) -> anyhow::Result<usize> {
+    if value < 0 {
+        return Err(anyhow!("Position {} is negative", value));
+    }
+
+    Ok(value as usize)
+}
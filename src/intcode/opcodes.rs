@@ -0,0 +1,226 @@
+// Instruction set of the Intcode machine. Every opcode is described by its parameters and a
+// handler, the standard instructions are registered the same way as any custom one.
+use std::fmt;
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use super::Machine;
+
+// Opcodes take the two lowest decimal digits of an instruction
+const OPCODE_COUNT: usize = 100;
+// Mode digits an isize holds above the two opcode digits
+const MAX_PARAMS: usize = 17;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Param {
+    // Handler gets the value of the parameter in any mode
+    Read,
+    // Handler gets the address to write to. Immediate mode is rejected.
+    Write,
+}
+
+// What the machine should do after a handler returns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    // Continue with the instruction following this one
+    Next,
+    Jump(usize),
+    // Continue with the next instruction and report the value
+    Output(isize),
+    // Input was not available, the instruction is retried on the next run
+    Wait,
+    Halt,
+}
+
+pub type Handler = Arc<dyn Fn(&mut Machine, &[isize]) -> anyhow::Result<Flow> + Send + Sync>;
+
+#[derive(Clone)]
+pub struct Opcode {
+    name: String,
+    params: Vec<Param>,
+    handler: Handler,
+}
+
+impl Opcode {
+    // Handler receives resolved parameters in the order they are declared in `params`
+    pub fn new<F>(name: &str, params: &[Param], handler: F) -> Opcode
+    where F: Fn(&mut Machine, &[isize]) -> anyhow::Result<Flow> + Send + Sync + 'static {
+        Opcode { name: name.to_owned(), params: params.to_vec(), handler: Arc::new(handler) }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn params(&self) -> &[Param] {
+        &self.params
+    }
+
+    pub fn arity(&self) -> usize {
+        self.params.len()
+    }
+
+    pub fn handler(&self) -> &Handler {
+        &self.handler
+    }
+}
+
+impl fmt::Debug for Opcode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Opcode").field("name", &self.name).field("params", &self.params).finish()
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RegistryError {
+    #[error("Opcode {0} does not fit in two digits")]
+    OutOfRange(isize),
+    #[error("Opcode {0} is already registered as {1}")]
+    Conflict(isize, String),
+    #[error("Opcode {0} takes {1} parameters, at most {2} fit in an instruction")]
+    TooManyParams(isize, usize, usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct InstructionSet {
+    opcodes: Vec<Option<Opcode>>,
+}
+
+impl InstructionSet {
+    pub fn empty() -> InstructionSet {
+        InstructionSet { opcodes: vec![None; OPCODE_COUNT] }
+    }
+
+    // Instructions every Intcode program of the challenges expects
+    pub fn standard() -> InstructionSet {
+        use Param::*;
+
+        let mut set = InstructionSet::empty();
+
+        let standard = vec![
+            (1, Opcode::new("add", &[Read, Read, Write], |m, p| {
+                let sum = p[0].checked_add(p[1]).ok_or_else(|| anyhow!("{} + {} overflows at {}", p[0], p[1], m.ip()))?;
+                m.write(p[2] as usize, sum);
+                Ok(Flow::Next)
+            })),
+            (2, Opcode::new("mul", &[Read, Read, Write], |m, p| {
+                let product = p[0].checked_mul(p[1]).ok_or_else(|| anyhow!("{} * {} overflows at {}", p[0], p[1], m.ip()))?;
+                m.write(p[2] as usize, product);
+                Ok(Flow::Next)
+            })),
+            (3, Opcode::new("in", &[Write], |m, p| {
+                match m.take_input() {
+                    Some(value) => { m.write(p[0] as usize, value); Ok(Flow::Next) },
+                    None => Ok(Flow::Wait),
+                }
+            })),
+            (4, Opcode::new("out", &[Read], |_, p| Ok(Flow::Output(p[0])))),
+            (5, Opcode::new("jnz", &[Read, Read], |_, p| jump_if(p[0] != 0, p[1]))),
+            (6, Opcode::new("jz", &[Read, Read], |_, p| jump_if(p[0] == 0, p[1]))),
+            (7, Opcode::new("lt", &[Read, Read, Write], |m, p| { m.write(p[2] as usize, (p[0] < p[1]) as isize); Ok(Flow::Next) })),
+            (8, Opcode::new("eq", &[Read, Read, Write], |m, p| { m.write(p[2] as usize, (p[0] == p[1]) as isize); Ok(Flow::Next) })),
+            (9, Opcode::new("arb", &[Read], |m, p| { m.adjust_relative_base(p[0])?; Ok(Flow::Next) })),
+            (99, Opcode::new("halt", &[], |_, _| Ok(Flow::Halt))),
+        ];
+
+        for (code, opcode) in standard {
+            set.register(code, opcode).expect("standard opcodes do not conflict");
+        }
+
+        set
+    }
+
+    // Fails if the opcode is taken, use `replace` to override an instruction on purpose
+    pub fn register(&mut self, code: isize, opcode: Opcode) -> Result<(), RegistryError> {
+        if let Some(existing) = self.get(code) {
+            return Err(RegistryError::Conflict(code, existing.name.clone()));
+        }
+
+        self.replace(code, opcode).map(|_| ())
+    }
+
+    // Returns the instruction that was registered under the opcode before
+    pub fn replace(&mut self, code: isize, opcode: Opcode) -> Result<Option<Opcode>, RegistryError> {
+        if !(0..OPCODE_COUNT as isize).contains(&code) {
+            return Err(RegistryError::OutOfRange(code));
+        }
+        if opcode.arity() > MAX_PARAMS {
+            return Err(RegistryError::TooManyParams(code, opcode.arity(), MAX_PARAMS));
+        }
+
+        Ok(self.opcodes[code as usize].replace(opcode))
+    }
+
+    pub fn get(&self, code: isize) -> Option<&Opcode> {
+        if code < 0 {
+            return None;
+        }
+
+        self.opcodes.get(code as usize).and_then(|opcode| opcode.as_ref())
+    }
+}
+
+impl Default for InstructionSet {
+    fn default() -> InstructionSet {
+        InstructionSet::standard()
+    }
+}
+
+fn jump_if(condition: bool, target: isize) -> anyhow::Result<Flow> {
+    if !condition {
+        return Ok(Flow::Next);
+    }
+
+    if target < 0 {
+        return Err(anyhow!("Position {} is negative", target));
+    }
+
+    Ok(Flow::Jump(target as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conflicting_registration_is_rejected() {
+        let mut set = InstructionSet::standard();
+
+        let result = set.register(1, Opcode::new("sub", &[Param::Read, Param::Read, Param::Write], |_, _| Ok(Flow::Next)));
+        assert_eq!(result, Err(RegistryError::Conflict(1, "add".to_string())));
+        assert_eq!(set.get(1).map(Opcode::name), Some("add"));
+
+        let replaced = set.replace(1, Opcode::new("sub", &[Param::Read, Param::Read, Param::Write], |_, _| Ok(Flow::Next)));
+        assert_eq!(replaced.unwrap().map(|opcode| opcode.name().to_string()), Some("add".to_string()));
+        assert_eq!(set.get(1).map(Opcode::name), Some("sub"));
+    }
+
+    #[test]
+    fn registered_opcode_runs() {
+        let mut set = InstructionSet::standard();
+        set.register(20, Opcode::new("double", &[Param::Read, Param::Write], |m, p| { m.write(p[1] as usize, p[0] * 2); Ok(Flow::Next) }))
+            .unwrap();
+
+        let mut machine = Machine::with_instructions(vec![120, 21, 6, 4, 6, 99, 0], Arc::new(set));
+        assert_eq!(machine.run_with_input(&[]).unwrap(), vec![42]);
+    }
+
+    #[test]
+    fn out_of_range_opcode_is_rejected() {
+        let mut set = InstructionSet::empty();
+
+        assert_eq!(set.register(100, Opcode::new("far", &[], |_, _| Ok(Flow::Halt))), Err(RegistryError::OutOfRange(100)));
+    }
+
+    #[test]
+    fn opcodes_with_more_params_than_mode_digits_are_rejected() {
+        let mut set = InstructionSet::empty();
+
+        assert!(set.register(50, Opcode::new("wide", &[Param::Read; MAX_PARAMS], |_, _| Ok(Flow::Next))).is_ok());
+        assert_eq!(super::super::parameter_mode(2 * 10isize.pow(MAX_PARAMS as u32 + 1) + 50, MAX_PARAMS), 2);
+
+        let result = set.register(51, Opcode::new("wider", &[Param::Read; MAX_PARAMS + 1], |_, _| Ok(Flow::Next)));
+        assert_eq!(result, Err(RegistryError::TooManyParams(51, MAX_PARAMS + 1, MAX_PARAMS)));
+    }
+}
@@ -0,0 +1,373 @@
+// Runs every Intcode machine on its own OS thread. Machines talk through `mpsc` channels: a
+// machine reads its input from an `Inbox` and sends its output down a `Wire`. Wires that lead to
+// another machine's inbox are counted by the `Supervisor`, which lets it tell when nothing can
+// make progress anymore: every machine is halted or waiting for input and no value is in flight.
+//
+// An amplifier chain is a row of machines where the wire of the n-th machine leads to the inbox
+// of the n+1-th one, a feedback loop additionally wires the last machine back to the first. In a
+// network a machine addresses its peers itself, so its outputs go through a `Router` which picks
+// the wire for every value, see `Supervisor::spawn_routed`. The caller reads results through a
+// tap on a wire, see `Wire::tap`.
+use std::sync::{mpsc, Arc, Condvar, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use super::{Event, Machine};
+
+// How often a machine waiting for input checks whether it should stop
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    // Every machine halted
+    Halted,
+    // At least one machine waits for input which nobody is going to send
+    Blocked,
+    // At least one machine stopped with an error or panicked, `Supervisor::join` returns the error
+    Failed,
+}
+
+#[derive(Default)]
+struct Counters {
+    running: usize,
+    blocked: usize,
+    failed: usize,
+    in_flight: usize, // values sent to an inbox but not yet received
+}
+
+#[derive(Default)]
+struct Shared {
+    counters: Mutex<Counters>,
+    changed: Condvar,
+    stop: AtomicBool,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Counters> {
+        // Counters are updated in single statements, so a poisoned lock still holds valid data
+        self.counters.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+// Sending half of a channel. Values sent to a machine are counted by the supervisor, values sent
+// to a plain `mpsc::Sender` (see `From`) leave the supervised network.
+#[derive(Clone)]
+pub struct Wire {
+    tx: mpsc::Sender<isize>,
+    shared: Option<Arc<Shared>>,
+    tap: Option<mpsc::Sender<isize>>,
+}
+
+impl Wire {
+    // Copies every value sent down the wire to the returned receiver, also the values the other
+    // side never gets because its machine has already halted. The last output of a feedback loop
+    // is read this way once the supervisor reports that the machines halted.
+    pub fn tap(&mut self) -> mpsc::Receiver<isize> {
+        let (tx, rx) = mpsc::channel();
+        self.tap = Some(tx);
+
+        rx
+    }
+
+    // Returns false if the receiving side is gone
+    pub fn send(&self, value: isize) -> bool {
+        if let Some(tap) = &self.tap {
+            // Nobody listening to the tap is not an error
+            let _ = tap.send(value);
+        }
+
+        match &self.shared {
+            Some(shared) => {
+                // Counting and sending under the lock keeps the count in sync with a machine
+                // draining its inbox when it halts
+                let mut counters = shared.lock();
+                let sent = self.tx.send(value).is_ok();
+                if sent {
+                    counters.in_flight += 1;
+                }
+
+                sent
+            },
+            None => self.tx.send(value).is_ok(),
+        }
+    }
+}
+
+impl From<mpsc::Sender<isize>> for Wire {
+    fn from(tx: mpsc::Sender<isize>) -> Wire {
+        Wire { tx, shared: None, tap: None }
+    }
+}
+
+// Handles every output of a machine on the machine's thread, usually by sending it down one of
+// several wires. Values sent from here are counted before the machine can block again.
+pub type Router = Box<dyn FnMut(isize) + Send>;
+
+// Receiving half of a channel created by `Supervisor::channel`
+pub struct Inbox {
+    rx: mpsc::Receiver<isize>,
+}
+
+#[derive(Default)]
+pub struct Supervisor {
+    shared: Arc<Shared>,
+    handles: Vec<thread::JoinHandle<anyhow::Result<Machine>>>,
+}
+
+impl Supervisor {
+    pub fn new() -> Supervisor {
+        Supervisor::default()
+    }
+
+    pub fn channel(&self) -> (Wire, Inbox) {
+        let (tx, rx) = mpsc::channel();
+
+        (Wire { tx, shared: Some(self.shared.clone()), tap: None }, Inbox { rx })
+    }
+
+    // Starts the machine on a new thread with every output sent down the wire. Machines are
+    // numbered in the order they were spawned.
+    pub fn spawn(&mut self, machine: Machine, inbox: Inbox, output: Wire) -> usize {
+        let id = self.handles.len();
+
+        self.spawn_routed(machine, inbox, Box::new(move |value| {
+            if !output.send(value) {
+                debug!("Machine {} output {} was not received", id, value);
+            }
+        }))
+    }
+
+    // Starts the machine on a new thread with its outputs handed to the router
+    pub fn spawn_routed(&mut self, machine: Machine, inbox: Inbox, router: Router) -> usize {
+        self.shared.lock().running += 1;
+
+        let shared = self.shared.clone();
+        let id = self.handles.len();
+
+        self.handles.push(thread::spawn(move || drive(id, machine, inbox, router, shared)));
+
+        id
+    }
+
+    // Blocks until no machine can make progress
+    pub fn wait(&self) -> Outcome {
+        let mut counters = self.shared.lock();
+
+        while counters.running > 0 || counters.in_flight > 0 {
+            counters = self.shared.changed.wait(counters).unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+
+        if counters.failed > 0 {
+            Outcome::Failed
+        } else if counters.blocked > 0 {
+            Outcome::Blocked
+        } else {
+            Outcome::Halted
+        }
+    }
+
+    // Stops machines still waiting for input and returns the final state of every machine in
+    // spawn order
+    pub fn join(self) -> anyhow::Result<Vec<Machine>> {
+        self.shared.stop.store(true, Ordering::SeqCst);
+
+        let mut machines = Vec::with_capacity(self.handles.len());
+        for (id, handle) in self.handles.into_iter().enumerate() {
+            let machine = handle.join().map_err(|_| anyhow!("Machine {} panicked", id))??;
+            machines.push(machine);
+        }
+
+        Ok(machines)
+    }
+}
+
+fn drive(id: usize, mut machine: Machine, inbox: Inbox, mut router: Router, shared: Arc<Shared>) -> anyhow::Result<Machine> {
+    let mut guard = Running { shared, inbox: Some(inbox), blocked: false, failed: false };
+
+    let result = execute(id, &mut machine, &mut guard, &mut router);
+    guard.failed = result.is_err();
+
+    result.map(|_| machine)
+}
+
+// Counts the machine as running for as long as its thread lives, also when the thread panics,
+// so the supervisor never waits for a machine that is gone
+struct Running {
+    shared: Arc<Shared>,
+    inbox: Option<Inbox>, // only taken when the thread ends
+    blocked: bool,
+    failed: bool, // the machine returned an error, a panic is told by `thread::panicking`
+}
+
+impl Running {
+    fn receive(&self) -> Result<isize, mpsc::RecvTimeoutError> {
+        let inbox = self.inbox.as_ref().expect("inbox lives as long as the machine runs");
+
+        inbox.rx.recv_timeout(POLL_INTERVAL)
+    }
+
+    fn block(&mut self) {
+        {
+            let mut counters = self.shared.lock();
+            counters.running -= 1;
+            counters.blocked += 1;
+        }
+        self.blocked = true;
+        self.shared.changed.notify_all();
+    }
+
+    // Called with the value the machine was waiting for, which is no longer in flight
+    fn unblock(&mut self, received: bool) {
+        {
+            let mut counters = self.shared.lock();
+            counters.blocked -= 1;
+            counters.running += 1;
+            if received {
+                counters.in_flight -= 1;
+            }
+        }
+        self.blocked = false;
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        {
+            let mut counters = self.shared.lock();
+
+            // Values left in the inbox will never be read. Draining and dropping the inbox under
+            // the lock makes sure no wire counts a value after the drain, later sends fail.
+            if let Some(inbox) = self.inbox.take() {
+                while inbox.rx.try_recv().is_ok() {
+                    counters.in_flight -= 1;
+                }
+                drop(inbox);
+            }
+
+            if self.blocked {
+                counters.blocked -= 1;
+            } else {
+                counters.running -= 1;
+            }
+            if self.failed || thread::panicking() {
+                counters.failed += 1;
+            }
+        }
+        self.shared.changed.notify_all();
+    }
+}
+
+fn execute(id: usize, machine: &mut Machine, running: &mut Running, router: &mut Router) -> anyhow::Result<()> {
+    loop {
+        match machine.run()? {
+            Event::Output(value) => router(value),
+            Event::Input => {
+                running.block();
+
+                let value = loop {
+                    match running.receive() {
+                        Ok(value) => break value,
+                        Err(error) => {
+                            if running.shared.stop.load(Ordering::SeqCst) {
+                                running.unblock(false);
+                                return Ok(());
+                            }
+
+                            // Nothing can arrive once every wire is dropped, the machine stays
+                            // blocked until the supervisor stops it
+                            if error == mpsc::RecvTimeoutError::Disconnected {
+                                thread::sleep(POLL_INTERVAL);
+                            }
+                        },
+                    }
+                };
+
+                running.unblock(true);
+                machine.push_input(value);
+            },
+            Event::Halt => return Ok(()),
+            Event::Watchpoint(hit) => { debug!("Machine {} hit a watchpoint: {}", id, hit); },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::opcodes::{InstructionSet, Opcode};
+
+    #[test]
+    fn blocked() {
+        let mut supervisor = Supervisor::new();
+        let (_wire, inbox) = supervisor.channel();
+        let (output, _) = supervisor.channel();
+
+        supervisor.spawn(Machine::new(vec![3, 0, 99]), inbox, output);
+
+        assert_eq!(supervisor.wait(), Outcome::Blocked);
+        assert!(supervisor.join().is_ok());
+    }
+
+    // Machine 0 sends (destination, value) packets, the others echo what they receive
+    #[test]
+    fn routed_packets_reach_their_destination() {
+        let mut supervisor = Supervisor::new();
+        let channels: Vec<(Wire, Inbox)> = (0..3).map(|_| supervisor.channel()).collect();
+        let wires: Vec<Wire> = channels.iter().map(|(wire, _)| wire.clone()).collect();
+        let mut results = supervisor.channel().0;
+        let echoed = results.tap();
+
+        let mut packet = Vec::new();
+        let peers = wires.clone();
+        let router: Router = Box::new(move |value| {
+            packet.push(value);
+            if let [destination, value] = packet[..] {
+                peers[destination as usize].send(value);
+                packet.clear();
+            }
+        });
+
+        let mut channels = channels.into_iter().map(|(_, inbox)| inbox);
+        supervisor.spawn_routed(Machine::new(vec![104, 2, 104, 5, 104, 1, 104, 6, 99]), channels.next().unwrap(), router);
+        for inbox in channels {
+            supervisor.spawn(Machine::new(vec![3, 0, 4, 0, 99]), inbox, results.clone());
+        }
+        drop(wires);
+        drop(results);
+
+        assert_eq!(supervisor.wait(), Outcome::Halted);
+        supervisor.join().unwrap();
+
+        let mut echoed: Vec<isize> = echoed.try_iter().collect();
+        echoed.sort_unstable();
+        assert_eq!(echoed, vec![5, 6]);
+    }
+
+    #[test]
+    fn failing_machine_is_reported() {
+        let mut supervisor = Supervisor::new();
+        let (_wire, inbox) = supervisor.channel();
+        let (output, _) = supervisor.channel();
+
+        supervisor.spawn(Machine::new(vec![42]), inbox, output);
+
+        assert_eq!(supervisor.wait(), Outcome::Failed);
+        assert!(supervisor.join().is_err());
+    }
+
+    #[test]
+    fn panicking_machine_is_not_waited_for() {
+        let mut instructions = InstructionSet::standard();
+        instructions.register(50, Opcode::new("panic", &[], |_, _| panic!("handler panicked"))).unwrap();
+
+        let mut supervisor = Supervisor::new();
+        let (_wire, inbox) = supervisor.channel();
+        let (output, _) = supervisor.channel();
+
+        supervisor.spawn(Machine::with_instructions(vec![50, 99], Arc::new(instructions)), inbox, output);
+
+        assert_eq!(supervisor.wait(), Outcome::Failed);
+        assert!(supervisor.join().is_err());
+    }
+}
@@ -0,0 +1,84 @@
+// Watchpoints on memory of the Intcode machine. A watchpoint covers a range of addresses and
+// either pauses the machine or logs every access the program makes to it.
+use std::fmt;
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Watch {
+    Reads,
+    Writes,
+    // Both reads and writes
+    All,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    // Machine stops with `Event::Watchpoint` once the instruction is done
+    Pause,
+    // Access is logged and the machine keeps running
+    Log,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub addresses: RangeInclusive<usize>,
+    pub watch: Watch,
+    pub action: Action,
+}
+
+impl Watchpoint {
+    pub fn new(addresses: RangeInclusive<usize>, watch: Watch, action: Action) -> Watchpoint {
+        Watchpoint { addresses, watch, action }
+    }
+
+    pub fn matches(&self, address: usize, access: Access) -> bool {
+        let watched = match self.watch {
+            Watch::Reads => access == Access::Read,
+            Watch::Writes => access == Access::Write,
+            Watch::All => true,
+        };
+
+        watched && self.addresses.contains(&address)
+    }
+}
+
+impl fmt::Display for Watchpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let watch = match self.watch {
+            Watch::Reads => "reads",
+            Watch::Writes => "writes",
+            Watch::All => "reads and writes",
+        };
+        let action = match self.action {
+            Action::Pause => "pause",
+            Action::Log => "log",
+        };
+
+        write!(f, "{} of {}..={} ({})", watch, self.addresses.start(), self.addresses.end(), action)
+    }
+}
+
+// Single access to a watched address. For reads the old and new values are the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hit {
+    pub ip: usize, // address of the instruction that made the access
+    pub address: usize,
+    pub access: Access,
+    pub old: isize,
+    pub new: isize,
+}
+
+impl fmt::Display for Hit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.access {
+            Access::Read => write!(f, "ip {} read {} from {}", self.ip, self.old, self.address),
+            Access::Write => write!(f, "ip {} wrote {} to {} (was {})", self.ip, self.new, self.address, self.old),
+        }
+    }
+}
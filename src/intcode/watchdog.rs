@@ -0,0 +1,129 @@
+// Watchdog stopping Intcode programs that run for too long, most likely because they loop
+// forever. When it fires, it reports where the program spent its time.
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::time::Duration;
+
+use super::opcodes::InstructionSet;
+
+// Length of the instruction history kept for the report
+const RECENT_LENGTH: usize = 16;
+// Number of most executed addresses in the report
+const HOTTEST_LENGTH: usize = 5;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    pub max_steps: Option<u64>,
+    // Time spent executing instructions, waiting for input does not count
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    Steps(u64),
+    Timeout(Duration),
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Reason::Steps(steps) => write!(f, "Step limit of {} reached", steps),
+            Reason::Timeout(timeout) => write!(f, "Timeout of {:?} reached", timeout),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WatchdogError {
+    pub reason: Reason,
+    pub ip: usize,
+    pub steps: u64,
+    pub hottest: Vec<(usize, u64)>, // address and how many times it was executed
+    pub recent: Vec<(usize, String)>, // address and the instruction, oldest first
+}
+
+impl fmt::Display for WatchdogError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} at ip {} after {} steps", self.reason, self.ip, self.steps)?;
+
+        writeln!(f, "Most executed addresses:")?;
+        for (address, count) in self.hottest.iter() {
+            writeln!(f, "  {:>6}: {} times", address, count)?;
+        }
+
+        write!(f, "Recent instructions:")?;
+        for (address, instruction) in self.recent.iter() {
+            write!(f, "\n  {:>6}: {}", address, instruction)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for WatchdogError {}
+
+#[derive(Debug, Clone)]
+pub(super) struct Watchdog {
+    limits: Limits,
+    steps: u64,
+    busy: Duration, // time spent in finished runs
+    executed: HashMap<usize, u64>,
+    recent: VecDeque<(usize, isize)>, // address and the instruction word
+}
+
+impl Watchdog {
+    pub fn new(limits: Limits) -> Watchdog {
+        Watchdog {
+            limits,
+            steps: 0,
+            busy: Duration::default(),
+            executed: HashMap::new(),
+            recent: VecDeque::with_capacity(RECENT_LENGTH),
+        }
+    }
+
+    // Called before the instruction at ip is executed
+    pub fn check_steps(&self) -> Option<Reason> {
+        match self.limits.max_steps {
+            Some(max_steps) if self.steps >= max_steps => Some(Reason::Steps(max_steps)),
+            _ => None,
+        }
+    }
+
+    // Called with the duration of the run in progress
+    pub fn check_time(&self, running: Duration) -> Option<Reason> {
+        match self.limits.timeout {
+            Some(timeout) if self.busy + running >= timeout => Some(Reason::Timeout(timeout)),
+            _ => None,
+        }
+    }
+
+    pub fn add_busy(&mut self, running: Duration) {
+        self.busy += running;
+    }
+
+    pub fn record(&mut self, ip: usize, instruction: isize) {
+        self.steps += 1;
+        *self.executed.entry(ip).or_insert(0) += 1;
+
+        if self.recent.len() == RECENT_LENGTH {
+            self.recent.pop_front();
+        }
+        self.recent.push_back((ip, instruction));
+    }
+
+    pub fn error(&self, reason: Reason, ip: usize, instructions: &InstructionSet) -> WatchdogError {
+        let mut hottest: Vec<(usize, u64)> = self.executed.iter().map(|(address, count)| (*address, *count)).collect();
+        hottest.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        hottest.truncate(HOTTEST_LENGTH);
+
+        let recent = self.recent.iter()
+            .map(|(address, instruction)| {
+                let name = instructions.get(instruction % 100).map_or("?", |opcode| opcode.name());
+                (*address, format!("{} ({})", name, instruction))
+            })
+            .collect();
+
+        WatchdogError { reason, ip, steps: self.steps, hottest, recent }
+    }
+}
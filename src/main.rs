@@ -5,18 +5,47 @@ extern crate pretty_env_logger;
 #[macro_use] extern crate itertools;
 #[macro_use] extern crate lazy_static;
 
-use clap::{App, Arg, SubCommand};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::time::Duration;
 
+// Lints the first days were written before, kept as they are
+#[allow(clippy::single_component_path_imports)]
 mod day_1;
+#[allow(clippy::single_component_path_imports, clippy::get_first, clippy::ptr_arg)]
 mod day_2;
+#[allow(clippy::single_component_path_imports, clippy::get_first, clippy::needless_borrow, clippy::ptr_arg)]
 mod day_3;
+#[allow(clippy::single_component_path_imports, clippy::iter_nth_zero, clippy::get_first)]
 mod day_4;
 mod day_5;
+#[allow(clippy::single_component_path_imports, redundant_semicolons, clippy::get_first, clippy::needless_lifetimes,
+    clippy::comparison_to_empty)]
 mod day_6;
+mod day_7;
+mod day_8;
+mod day_9;
+mod day_10;
+mod day_11;
+mod day_12;
+mod day_13;
+mod day_14;
+mod day_15;
+mod day_16;
+mod day_17;
+mod day_18;
+mod day_19;
+mod day_20;
+mod day_21;
+mod day_22;
+mod day_24;
+mod day_25;
+mod intcode;
 
 lazy_static! {
-    static ref SUBCOMMANDS: Vec<&'static str> = vec!["day1_1", "day1_2", "day2_1", "day2_2", "day3_1",
-    "day3_2", "day4_1", "day4_2", "day5", "day6_1", "day6_2"];
+    static ref SUBCOMMANDS: Vec<&'static str> = vec!["day2_1", "day2_2", "day3_1", "day3_2", "day4_1", "day4_2",
+    "day6_1", "day6_2", "day7_1", "day7_2", "day9_1", "day9_2", "day10_1", "day11_1", "day11_2", "day12_2", "day13_1",
+    "day14_1", "day14_2", "day15", "day16_1", "day16_2", "day17_1", "day17_2", "day18_1", "day18_2", "day19_1",
+    "day19_2", "day20_1", "day22_1", "day22_2", "day24_1"];
 }
 
 fn main() {
@@ -36,6 +65,117 @@ fn main() {
             app = app.subcommand(SubCommand::with_name(subcommand));
         }
 
+        let lenient = Arg::with_name("lenient")
+            .help("Skip invalid masses instead of failing")
+            .long("lenient");
+
+        app = app.subcommand(SubCommand::with_name("day1_1").arg(lenient.clone()));
+        app = app.subcommand(SubCommand::with_name("day1_2").arg(lenient.clone()));
+        app = app.subcommand(SubCommand::with_name("day1_report")
+            .about("Fuel of every module")
+            .arg(Arg::with_name("format")
+                .help("Output format")
+                .long("format")
+                .possible_values(&["table", "csv", "json"])
+                .default_value("table"))
+            .arg(lenient));
+
+        app = app.subcommand(SubCommand::with_name("day5")
+            .arg(Arg::with_name("max-steps")
+                .help("Stop the program after this many instructions")
+                .long("max-steps")
+                .takes_value(true))
+            .arg(Arg::with_name("timeout")
+                .help("Stop the program after running for this many seconds")
+                .long("timeout")
+                .takes_value(true))
+            .arg(Arg::with_name("coverage")
+                .help("Write disassembly annotated with the coverage of the run to the file")
+                .long("coverage")
+                .takes_value(true))
+            .arg(Arg::with_name("coverage-summary")
+                .help("Write coverage of the run as JSON to the file")
+                .long("coverage-summary")
+                .takes_value(true)));
+
+        for subcommand in ["day8_1", "day8_2"].iter() {
+            let mut command = SubCommand::with_name(subcommand)
+                .arg(Arg::with_name("width")
+                    .help("Width of the image in pixels")
+                    .long("width")
+                    .default_value("25"))
+                .arg(Arg::with_name("height")
+                    .help("Height of the image in pixels")
+                    .long("height")
+                    .default_value("6"));
+
+            if *subcommand == "day8_2" {
+                command = command.arg(Arg::with_name("export")
+                    .help("Save the decoded image to a .pgm or .png file")
+                    .long("export")
+                    .takes_value(true));
+            }
+
+            app = app.subcommand(command);
+        }
+
+        app = app.subcommand(SubCommand::with_name("day10_2")
+            .arg(Arg::with_name("order")
+                .help("Print every asteroid in the order it is vaporised")
+                .long("order")));
+
+        app = app.subcommand(SubCommand::with_name("day12_1")
+            .arg(Arg::with_name("steps")
+                .help("Number of steps to simulate")
+                .long("steps")
+                .default_value("1000")));
+
+        app = app.subcommand(SubCommand::with_name("day13_2")
+            .arg(Arg::with_name("watch")
+                .help("Redraw the screen every frame")
+                .long("watch")));
+
+        app = app.subcommand(SubCommand::with_name("day20_2")
+            .arg(Arg::with_name("max-depth")
+                .help("Deepest level the path may go to")
+                .long("max-depth")
+                .default_value("100")));
+
+        app = app.subcommand(SubCommand::with_name("day21")
+            .arg(Arg::with_name("script")
+                .help("Springscript file to run, ending with WALK or RUN")
+                .long("script")
+                .takes_value(true)
+                .required_unless("search"))
+            .arg(Arg::with_name("search")
+                .help("Search for a script that survives")
+                .long("search")
+                .conflicts_with("script"))
+            .arg(Arg::with_name("run")
+                .help("Search for a RUN script instead of WALK")
+                .long("run")
+                .requires("search")));
+
+        app = app.subcommand(SubCommand::with_name("day24_2")
+            .arg(Arg::with_name("minutes")
+                .help("Number of minutes to simulate")
+                .long("minutes")
+                .default_value("200")));
+
+        app = app.subcommand(SubCommand::with_name("day25")
+            .arg(Arg::with_name("solve")
+                .help("Explore the ship and get through the checkpoint without help")
+                .long("solve")));
+
+        app = app.subcommand(SubCommand::with_name("debug")
+            .about("Debug an Intcode program")
+            .arg(Arg::with_name("set")
+                .help("Memory to change before the start as address=value")
+                .long("set")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)));
+
         app.get_matches()
     };
 
@@ -43,17 +183,97 @@ fn main() {
     info!("Using file \"{}\" as input.", filepath);
 
     let command_result = match matches.subcommand() {
-        ("day1_1", _) => { day_1::calculate_fuel(filepath.to_string()) },
-        ("day1_2", _) => { day_1::calculate_fuel_extended(filepath.to_string()) },
+        ("day1_1", Some(args)) => { day_1::calculate_fuel(filepath.to_string(), args.is_present("lenient")) },
+        ("day1_2", Some(args)) => { day_1::calculate_fuel_extended(filepath.to_string(), args.is_present("lenient")) },
+        ("day1_report", Some(args)) => {
+            let format = match args.value_of("format") {
+                Some("csv") => day_1::Format::Csv,
+                Some("json") => day_1::Format::Json,
+                _ => day_1::Format::Table,
+            };
+            day_1::fuel_report(filepath.to_string(), format, args.is_present("lenient"))
+        },
         ("day2_1", _) => { day_2::program_alarm(filepath.to_string()) },
         ("day2_2", _) => { day_2::search_answer(filepath.to_string()) },
         ("day3_1", _) => { day_3::find_closest_crossing(filepath.to_string()) },
         ("day3_2", _) => { day_3::find_lowest_latency(filepath.to_string()) },
         ("day4_1", _) => { day_4::count_diffrent_passwords(filepath.to_string()) },
         ("day4_2", _) => { day_4::count_diffrent_passwords_part2(filepath.to_string()) },
-        ("day5", _)   => { day_5::diagnostic_tests(filepath.to_string()) },
+        ("day5", Some(args)) => {
+            limits(args).and_then(|limits| day_5::diagnostic_tests(filepath.to_string(), day_5::Options {
+                limits,
+                coverage: args.value_of("coverage").map(String::from),
+                coverage_summary: args.value_of("coverage-summary").map(String::from),
+            }))
+        },
         ("day6_1", _) => { day_6::total_orbit_count(filepath.to_string()) },
         ("day6_2", _) => { day_6::orbital_transfer_length(filepath.to_string()) },
+        ("day7_1", _) => { day_7::max_thruster_signal(filepath.to_string()) },
+        ("day7_2", _) => { day_7::max_feedback_signal(filepath.to_string()) },
+        ("day8_1", Some(args)) => {
+            dimensions(args).and_then(|(width, height)| day_8::image_checksum(filepath.to_string(), width, height))
+        },
+        ("day8_2", Some(args)) => {
+            dimensions(args).and_then(|(width, height)| {
+                day_8::decode_image(filepath.to_string(), width, height, args.value_of("export").map(String::from))
+            })
+        },
+        ("day9_1", _) => { day_9::boost_keycode(filepath.to_string()) },
+        ("day9_2", _) => { day_9::distress_coordinates(filepath.to_string()) },
+        ("day10_1", _) => { day_10::best_location(filepath.to_string()) },
+        ("day10_2", Some(args)) => { day_10::vaporisation_order(filepath.to_string(), args.is_present("order")) },
+        ("day11_1", _) => { day_11::count_painted_panels(filepath.to_string()) },
+        ("day11_2", _) => { day_11::registration_identifier(filepath.to_string()) },
+        ("day12_1", Some(args)) => {
+            args.value_of("steps").unwrap_or("1000").parse::<usize>().map_err(anyhow::Error::from)
+                .and_then(|steps| day_12::total_energy(filepath.to_string(), steps))
+        },
+        ("day12_2", _) => { day_12::find_repetition(filepath.to_string()) },
+        ("day13_1", _) => { day_13::count_blocks(filepath.to_string()) },
+        ("day14_1", _) => { day_14::ore_for_fuel(filepath.to_string()) },
+        ("day14_2", _) => { day_14::fuel_from_cargo(filepath.to_string()) },
+        ("day15", _) => { day_15::repair_oxygen_system(filepath.to_string()) },
+        ("day16_1", _) => { day_16::first_digits(filepath.to_string()) },
+        ("day16_2", _) => { day_16::real_message(filepath.to_string()) },
+        ("day17_1", _) => { day_17::alignment_parameters(filepath.to_string()) },
+        ("day17_2", _) => { day_17::collect_dust(filepath.to_string()) },
+        ("day18_1", _) => { day_18::collect_keys(filepath.to_string()) },
+        ("day18_2", _) => { day_18::collect_keys_with_robots(filepath.to_string()) },
+        ("day19_1", _) => { day_19::count_affected_points(filepath.to_string()) },
+        ("day19_2", _) => { day_19::fit_ship(filepath.to_string()) },
+        ("day20_1", _) => { day_20::shortest_path(filepath.to_string()) },
+        ("day20_2", Some(args)) => {
+            args.value_of("max-depth").unwrap_or("100").parse::<usize>().map_err(anyhow::Error::from)
+                .and_then(|max_depth| day_20::shortest_recursive_path(filepath.to_string(), max_depth))
+        },
+        ("day21", Some(args)) => {
+            match args.value_of("script") {
+                Some(script) => day_21::run_script(filepath.to_string(), script.to_string()),
+                None => {
+                    let mode = if args.is_present("run") { day_21::Mode::Run } else { day_21::Mode::Walk };
+                    day_21::search_script(filepath.to_string(), mode)
+                },
+            }
+        },
+        ("day22_1", _) => { day_22::card_position(filepath.to_string()) },
+        ("day22_2", _) => { day_22::card_at_position(filepath.to_string()) },
+        ("day24_1", _) => { day_24::first_repeated_layout(filepath.to_string()) },
+        ("day24_2", Some(args)) => {
+            args.value_of("minutes").unwrap_or("200").parse::<usize>().map_err(anyhow::Error::from)
+                .and_then(|minutes| day_24::recursive_bugs(filepath.to_string(), minutes))
+        },
+        ("day25", Some(args)) => {
+            if args.is_present("solve") {
+                day_25::solve(filepath.to_string())
+            } else {
+                day_25::play(filepath.to_string())
+            }
+        },
+        ("day13_2", Some(args)) => { day_13::play(filepath.to_string(), args.is_present("watch")) },
+        ("debug", Some(args)) => {
+            let patches = args.values_of("set").map(|values| values.map(String::from).collect()).unwrap_or_default();
+            intcode::debugger::debug(filepath.to_string(), patches)
+        },
         _ => { Err(anyhow!("Challenge is unspecified")) },
     };
 
@@ -61,3 +281,19 @@ fn main() {
         error!("{}", error);
     }
 }
+
+fn limits(args: &ArgMatches) -> anyhow::Result<intcode::watchdog::Limits> {
+    let max_steps = args.value_of("max-steps").map(|steps| steps.parse::<u64>()).transpose()?;
+    let timeout = args.value_of("timeout").map(|seconds| seconds.parse::<f64>()).transpose()?
+        .map(|seconds| Duration::try_from_secs_f64(seconds).map_err(|error| anyhow!("Timeout {}: {}", seconds, error)))
+        .transpose()?;
+
+    Ok(intcode::watchdog::Limits { max_steps, timeout })
+}
+
+fn dimensions(args: &ArgMatches) -> anyhow::Result<(usize, usize)> {
+    let width = args.value_of("width").unwrap_or("25").parse::<usize>()?;
+    let height = args.value_of("height").unwrap_or("6").parse::<usize>()?;
+
+    Ok((width, height))
+}